@@ -0,0 +1,24 @@
+#!/usr/bin/env python3
+"""A bot for `--player bot:python3 bots/threshold.py`: hits below 17, doubles
+on 11 and bids a tenth of its money."""
+
+import json
+import sys
+
+for line in sys.stdin:
+    message = json.loads(line)
+    reply = {"id": message["id"]}
+    if message["type"] == "bid":
+        reply["bid"] = max(1, message["money"] // 10)
+    elif message["type"] == "action":
+        if message["total"] == 11 and "double" in message["allowed"]:
+            reply["action"] = "double"
+        elif message["total"] < 17:
+            reply["action"] = "hit"
+        else:
+            reply["action"] = "stand"
+    elif message["type"] == "redeal":
+        reply["redeal"] = True
+    elif message["type"] == "insurance":
+        reply["insurance"] = False
+    print(json.dumps(reply), flush=True)
@@ -0,0 +1,253 @@
+//! Exact odds of how the bank's hand ends when it draws to a fixed
+//! threshold, found by going through every order the remaining cards can
+//! come in. `bank-odds` mode prints them for an open card.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::rules::Rules;
+use crate::{exit_with, flag_values, parse_rules, parse_values, Card, Deck, Hand, Suit, Value};
+
+/// Chance of each way the bank's hand can end.
+#[derive(Clone, Copy, Default)]
+pub struct BankDistribution {
+  /// Chance of standing on each total, a natural not included
+  pub totals: [f64; 22],
+  /// 21 with the first two cards
+  pub natural: f64,
+  pub bust: f64,
+}
+
+impl BankDistribution {
+  fn add(&mut self, other: &BankDistribution, chance: f64) {
+    for (total, other) in self.totals.iter_mut().zip(other.totals) {
+      *total += other * chance;
+    }
+    self.natural += other.natural * chance;
+    self.bust += other.bust * chance;
+  }
+}
+
+/// Works out bank distributions under `rules` for a bank that stands on
+/// `stand_on` or more, and on soft 17 unless the rules say otherwise.
+/// Distributions are remembered per hand and cards left, so asking again for
+/// related hands is cheap.
+pub struct BankOdds<'a> {
+  rules: &'a Rules,
+  stand_on: u32,
+  memo: HashMap<([u32; 14], u32, u32, usize), BankDistribution>,
+}
+
+impl<'a> BankOdds<'a> {
+  pub fn new(rules: &'a Rules, stand_on: u32) -> BankOdds<'a> {
+    BankOdds {
+      rules,
+      stand_on,
+      memo: HashMap::new(),
+    }
+  }
+
+  /// How the bank's `hand` ends when it draws from `counts`, the cards left
+  /// per value from Ace to Joker. The bank always takes a second card.
+  pub fn distribution(&mut self, hand: &Hand, counts: &[u32; 14]) -> BankDistribution {
+    let mut counts = *counts;
+    self.finish(*hand, &mut counts)
+  }
+
+  fn finish(&mut self, hand: Hand, counts: &mut [u32; 14]) -> BankDistribution {
+    let mut distribution = BankDistribution::default();
+    if hand.is_bust() {
+      distribution.bust = 1.0;
+      return distribution;
+    }
+    let cards_left: u32 = counts.iter().sum();
+    if hand.cards().len() >= 2 && (!self.hits(&hand) || cards_left == 0) {
+      match hand.is_natural() {
+        true => distribution.natural = 1.0,
+        false => distribution.totals[hand.greatest_value() as usize] = 1.0,
+      }
+      return distribution;
+    }
+
+    // Only whether the hand is still two cards or less matters for a natural
+    let key = (*counts, hand.totals, hand.lowest, hand.cards().len().min(3));
+    if let Some(distribution) = self.memo.get(&key) {
+      return *distribution;
+    }
+    for index in 0..counts.len() {
+      let count = counts[index];
+      if count == 0 {
+        continue;
+      }
+      let mut next = hand;
+      let value = Value::from_u32(index as u32 + 1);
+      next.add_card(Card::new(Suit::Spades, value), &self.rules.worth);
+      counts[index] -= 1;
+      let after = self.finish(next, counts);
+      counts[index] += 1;
+      distribution.add(&after, count as f64 / cards_left as f64);
+    }
+    self.memo.insert(key, distribution);
+    distribution
+  }
+
+  fn hits(&self, hand: &Hand) -> bool {
+    let total = hand.greatest_value();
+    total < self.stand_on || (self.rules.bank_hits_soft_17 && total == 17 && hand.is_soft())
+  }
+}
+
+impl fmt::Display for BankDistribution {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (total, chance) in self.totals.iter().enumerate() {
+      if *chance > 0.0 {
+        writeln!(f, "  {:<9} {:>7.3}%", format!("{total}:"), chance * 100.0)?;
+      }
+    }
+    if self.natural > 0.0 {
+      writeln!(f, "  {:<9} {:>7.3}%", "natural:", self.natural * 100.0)?;
+    }
+    write!(f, "  {:<9} {:>7.3}%", "bust:", self.bust * 100.0)
+  }
+}
+
+/// `bank-odds <open card>`, e.g. `bank-odds 7`, with `--rule`,
+/// `--bank stand-on-<n>` and `--removed <cards>` for other cards known to be
+/// out of the deck.
+pub fn run(args: &[String]) {
+  let Some(open_card) = args.get(2) else {
+    exit_with("Usage: bank-odds <open card>, like: bank-odds 7");
+  };
+  let open_card: Value = open_card
+    .parse()
+    .unwrap_or_else(|error: String| exit_with(&error));
+  let stand_on = parse_stand_on(args);
+  let mut removed = vec![open_card];
+  if let Some(list) = flag_values(args, "--removed").first() {
+    removed.extend(parse_values(list).unwrap_or_else(|error| exit_with(&error)));
+  }
+
+  let rules = parse_rules(args);
+  let mut deck = Deck::new(&rules.deck, &rules.worth, StdRng::seed_from_u64(0));
+  deck
+    .remove(&removed)
+    .unwrap_or_else(|error| exit_with(&error));
+  let mut hand = Hand::new();
+  hand.add_card(Card::new(Suit::Spades, open_card), &rules.worth);
+
+  let distribution = BankOdds::new(&rules, stand_on).distribution(&hand, &deck.counts());
+  println!(
+    "Bank showing {}, standing on {}, {} cards left ({})",
+    open_card.to_string().trim(),
+    stand_on,
+    deck.cards.len(),
+    rules.variant.name()
+  );
+  println!("{distribution}");
+}
+
+/// The total a `--bank stand-on-<n>` bank stands on, 17 by default.
+pub fn parse_stand_on(args: &[String]) -> u32 {
+  match flag_values(args, "--bank").first() {
+    Some(name) => name
+      .strip_prefix("stand-on-")
+      .and_then(|threshold| threshold.parse().ok())
+      .unwrap_or_else(|| exit_with("Exact odds need a stand-on-<n> bank")),
+    None => 17,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::strategy::{FixedThreshold, StandOn};
+  use crate::{Game, Player};
+
+  fn french_counts(removed: &[Value]) -> [u32; 14] {
+    let mut counts = [4; 14];
+    counts[13] = 0;
+    for value in removed {
+      counts[value.to_u32() as usize - 1] -= 1;
+    }
+    counts
+  }
+
+  fn casino() -> Rules {
+    let mut rules = Rules::default();
+    rules.set("variant", "casino").unwrap();
+    rules
+  }
+
+  #[test]
+  fn chances_add_up_to_one() {
+    let rules = casino();
+    let mut odds = BankOdds::new(&rules, 17);
+    for value in &Value::ALL[..13] {
+      let mut hand = Hand::new();
+      hand.add_card(Card::new(Suit::Spades, *value), &rules.worth);
+      let distribution = odds.distribution(&hand, &french_counts(&[*value]));
+      let sum = distribution.totals.iter().sum::<f64>() + distribution.natural + distribution.bust;
+      assert!((sum - 1.0).abs() < 1e-9, "{value}: {sum}");
+      assert!(distribution.totals[..17]
+        .iter()
+        .all(|chance| *chance == 0.0));
+    }
+  }
+
+  #[test]
+  fn six_showing_busts_as_often_as_counted_by_hand() {
+    let rules = casino();
+    let mut hand = Hand::new();
+    hand.add_card(Card::new(Suit::Spades, Value::Six), &rules.worth);
+    let distribution = BankOdds::new(&rules, 17).distribution(&hand, &french_counts(&[Value::Six]));
+    // Single deck, standing on soft 17, as counted out separately
+    assert!(
+      (distribution.bust - 0.420823).abs() < 1e-6,
+      "{}",
+      distribution.bust
+    );
+  }
+
+  #[test]
+  fn matches_the_bank_in_play() {
+    let rules = casino();
+    // A player standing on 10 and 6 against an open 7
+    let removed = [Value::Ten, Value::Seven, Value::Six];
+    let mut hand = Hand::new();
+    hand.add_card(Card::new(Suit::Spades, Value::Seven), &rules.worth);
+    let exact = BankOdds::new(&rules, 17).distribution(&hand, &french_counts(&removed));
+
+    let mut bank = Player::new(0);
+    bank.money = 1_000_000_000;
+    let mut game = Game::new(
+      bank,
+      Box::new(StandOn::new(17)),
+      rules,
+      StdRng::seed_from_u64(5),
+    );
+    game.add_player(Player::new(1), Box::new(FixedThreshold::new(22.0)));
+    game
+      .stack(vec![Value::Ten, Value::Seven, Value::Six])
+      .unwrap();
+    let rounds = 100_000;
+    let mut played = BankDistribution::default();
+    for _ in 0..rounds {
+      game.play_round().unwrap();
+      match game.bank.hand.best_total() {
+        Some(total) => played.totals[total as usize] += 1.0 / rounds as f64,
+        None => played.bust += 1.0 / rounds as f64,
+      }
+      game.clean_up();
+    }
+    assert!((exact.bust - played.bust).abs() < 0.005);
+    for total in 17..=21 {
+      assert!(
+        (exact.totals[total] - played.totals[total]).abs() < 0.005,
+        "{total}"
+      );
+    }
+  }
+}
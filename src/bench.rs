@@ -0,0 +1,117 @@
+//! `bench` mode: plays a fixed, seeded workload on the engine and reports how
+//! fast it runs, so changes that slow the simulation down show up.
+
+use std::thread;
+use std::time::Instant;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::rules::Rules;
+use crate::strategy::{play_strategy_by_name, StandOn};
+use crate::{Card, Game, Hand, Player, DEFAULT_SEATS};
+
+/// Rounds every thread plays
+const ROUNDS: u32 = 1_000_000;
+const SEED: u64 = 21;
+
+/// What a thread ended up with, which is the same every run as long as the
+/// engine plays the same way.
+struct Digest {
+  hands: u32,
+  net: i64,
+}
+
+pub fn run() {
+  let max_threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+  println!(
+    "Benchmark: {}, {} seats ({}), stand-on(17), {} rounds per thread, seed {}",
+    Rules::default().variant.name(),
+    DEFAULT_SEATS.len(),
+    DEFAULT_SEATS.join(", "),
+    ROUNDS,
+    SEED
+  );
+  println!(
+    "Card: {} bytes, hand: {} bytes",
+    std::mem::size_of::<Card>(),
+    std::mem::size_of::<Hand>()
+  );
+  println!();
+  println!("  Threads   Rounds/s  Per thread  Scaling");
+
+  // Doubling up to every core the machine has
+  let mut thread_counts: Vec<usize> = std::iter::successors(Some(1), |threads| Some(threads * 2))
+    .take_while(|threads| *threads < max_threads)
+    .collect();
+  thread_counts.push(max_threads);
+
+  let mut single_thread = 0.0;
+  let mut digest = None;
+  for threads in thread_counts {
+    let start = Instant::now();
+    let digests = thread::scope(|scope| {
+      let handles: Vec<_> = (0..threads)
+        .map(|thread| scope.spawn(move || play(SEED + thread as u64)))
+        .collect();
+      handles
+        .into_iter()
+        .map(|handle| handle.join().expect("Benchmark thread panicked"))
+        .collect::<Vec<_>>()
+    });
+    let rounds_per_second = (ROUNDS as usize * threads) as f64 / start.elapsed().as_secs_f64();
+    if threads == 1 {
+      single_thread = rounds_per_second;
+    }
+    println!(
+      "  {:>7} {:>10.0} {:>11.0} {:>7.2}x",
+      threads,
+      rounds_per_second,
+      rounds_per_second / threads as f64,
+      rounds_per_second / single_thread
+    );
+    digest = digests.into_iter().next();
+  }
+
+  println!();
+  if let Some(digest) = digest {
+    println!(
+      "Result of the first thread: {} hands, players' net {}$",
+      digest.hands, digest.net
+    );
+  }
+  match peak_memory() {
+    Some(kib) => println!("Peak memory: {:.1} MiB", kib as f64 / 1024.0),
+    None => println!("Peak memory: unknown on this platform"),
+  }
+}
+
+/// Plays the workload on a fresh game seeded with `seed`.
+fn play(seed: u64) -> Digest {
+  let mut game = Game::new(
+    Player::new(0),
+    Box::new(StandOn::new(17)),
+    Rules::default(),
+    StdRng::seed_from_u64(seed),
+  );
+  for (index, name) in DEFAULT_SEATS.iter().enumerate() {
+    let strategy = play_strategy_by_name(name).expect("Unknown benchmark strategy");
+    game.add_player(Player::new(index as u32 + 1), strategy);
+  }
+  for _ in 0..ROUNDS {
+    game.play_round().expect("The benchmark deck ran out");
+    game.clean_up();
+  }
+  let records = game.records.borrow();
+  Digest {
+    hands: records.hands(),
+    net: records.net(),
+  }
+}
+
+/// Peak resident memory of the process in KiB, as Linux reports it.
+fn peak_memory() -> Option<u64> {
+  let status = std::fs::read_to_string("/proc/self/status").ok()?;
+  let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+  line.split_whitespace().nth(1)?.parse().ok()
+}
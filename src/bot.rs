@@ -0,0 +1,370 @@
+//! Strategies played by an external program, so bots can be written in any
+//! language. The program gets one JSON message per line on its standard
+//! input and answers each with one line of JSON on its standard output,
+//! repeating the message's `id`:
+//!
+//! ```text
+//! {"id":1,"type":"bid","money":100,"hand":["10"],"total":10,"soft":false,"bank_card":"7",
+//!  "deck_left":49,"deck_expected":5.7,"variant":"Dutch eenentwintigen"}
+//! {"id":1,"bid":10}
+//! {"id":2,"type":"action","hand":["10","6"],"total":16,"soft":false,"bank_card":"7",
+//!  "allowed":["hit","stand"],"deck_left":46,"deck_expected":5.6,"variant":"Dutch eenentwintigen"}
+//! {"id":2,"action":"hit"}
+//! {"id":3,"type":"redeal",...the fields of an action message but "allowed"}
+//! {"id":3,"redeal":true}
+//! {"id":4,"type":"insurance",...}
+//! {"id":4,"insurance":false}
+//! ```
+//!
+//! A bid comes with the cards dealt before the bids, none in a casino game.
+//! `bank_card` is `null` when the bank shows no card. A bot that answers too
+//! late, answers something that can't be read or picks an action that isn't
+//! allowed gets the cautious choice instead: bidding 1, standing, taking the
+//! redeal and no insurance. Every kind of problem is reported the first time
+//! it happens and counted when the bot is done. A bot that exits is no
+//! longer asked.
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value as Json};
+
+use crate::strategy::{Action, PlayStrategy, PlayerView};
+use crate::Player;
+
+/// How long a bot gets to answer a message
+pub const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A play strategy asking an external program for every decision.
+pub struct Bot {
+  name: String,
+  child: Child,
+  stdin: Option<ChildStdin>,
+  /// Lines the bot writes, read on their own thread so that waiting for them
+  /// can time out
+  lines: Receiver<String>,
+  timeout: Duration,
+  /// Id of the last message sent
+  id: u64,
+  problems: Problems,
+}
+
+/// How often a bot failed to answer properly, by kind.
+#[derive(Default)]
+struct Problems {
+  timeouts: u32,
+  unreadable: u32,
+  illegal: u32,
+  exited: bool,
+}
+
+impl Bot {
+  /// Starts `command`, a program followed by its arguments.
+  pub fn spawn(name: &str, command: &str, timeout: Duration) -> Result<Bot, String> {
+    let mut words = command.split_whitespace();
+    let program = words
+      .next()
+      .ok_or(format!("No command to run for bot '{name}'"))?;
+    let mut child = Command::new(program)
+      .args(words)
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .spawn()
+      .map_err(|error| format!("Could not start bot '{command}': {error}"))?;
+    let stdout = child.stdout.take().expect("The bot's output is piped");
+    let (sender, lines) = mpsc::channel();
+    thread::spawn(move || {
+      for line in BufReader::new(stdout).lines() {
+        let Ok(line) = line else {
+          break;
+        };
+        if sender.send(line).is_err() {
+          break;
+        }
+      }
+    });
+    Ok(Bot {
+      name: name.to_string(),
+      stdin: child.stdin.take(),
+      child,
+      lines,
+      timeout,
+      id: 0,
+      problems: Problems::default(),
+    })
+  }
+
+  fn report(&self, first: bool, problem: &str) {
+    if first {
+      eprintln!("Bot '{}' {problem}", self.name);
+    }
+  }
+
+  /// Sends `message` and returns the field `answer` of the reply, if the bot
+  /// gives one in time.
+  fn ask(&mut self, mut message: Json, answer: &str) -> Option<Json> {
+    if self.problems.exited {
+      return None;
+    }
+    self.id += 1;
+    message["id"] = json!(self.id);
+    let sent = self.stdin.as_mut().is_some_and(|stdin| {
+      writeln!(stdin, "{message}")
+        .and_then(|_| stdin.flush())
+        .is_ok()
+    });
+    if !sent {
+      self.exited();
+      return None;
+    }
+    loop {
+      let line = match self.lines.recv_timeout(self.timeout) {
+        Ok(line) => line,
+        Err(RecvTimeoutError::Timeout) => {
+          self.problems.timeouts += 1;
+          let first = self.problems.timeouts == 1;
+          self.report(
+            first,
+            &format!("took longer than {:?} to answer", self.timeout),
+          );
+          return None;
+        }
+        Err(RecvTimeoutError::Disconnected) => {
+          self.exited();
+          return None;
+        }
+      };
+      let reply: Option<Json> = serde_json::from_str(&line).ok();
+      match reply.as_ref().and_then(|reply| reply["id"].as_u64()) {
+        // A late answer to a message that timed out
+        Some(id) if id < self.id => continue,
+        Some(id) if id == self.id => {
+          let reply = reply.unwrap();
+          if reply.get(answer).is_some() {
+            return Some(reply[answer].clone());
+          }
+        }
+        _ => {}
+      }
+      self.problems.unreadable += 1;
+      let first = self.problems.unreadable == 1;
+      self.report(
+        first,
+        &format!(
+          "answered '{line}', expected \"{answer}\" and id {}",
+          self.id
+        ),
+      );
+      return None;
+    }
+  }
+
+  fn exited(&mut self) {
+    if !self.problems.exited {
+      self.problems.exited = true;
+      self.report(true, "exited, the rest of its decisions are made for it");
+    }
+  }
+
+  fn illegal(&mut self, answer: &Json) {
+    self.problems.illegal += 1;
+    let first = self.problems.illegal == 1;
+    self.report(first, &format!("answered {answer}, which is not allowed"));
+  }
+
+  /// What the bot sees of its hand, as sent along with every decision.
+  fn view(kind: &str, view: &PlayerView) -> Json {
+    let hand: Vec<String> = view
+      .hand
+      .cards()
+      .iter()
+      .map(|card| card.value().to_string().trim().to_string())
+      .collect();
+    json!({
+      "type": kind,
+      "hand": hand,
+      "total": view.hand.greatest_value(),
+      "soft": view.hand.is_soft(),
+      "bank_card": view.bank_card.map(|card| card.value().to_string().trim().to_string()),
+      "deck_left": view.deck.cards.len(),
+      "deck_expected": view.deck.expected_value(),
+      "variant": view.rules.variant.name(),
+    })
+  }
+
+  /// Asks a yes or no question about the hand, `default` if the bot can't
+  /// tell.
+  fn choice(&mut self, kind: &str, view: &PlayerView, default: bool) -> bool {
+    match self.ask(Bot::view(kind, view), kind) {
+      Some(Json::Bool(choice)) => choice,
+      Some(answer) => {
+        self.illegal(&answer);
+        default
+      }
+      None => default,
+    }
+  }
+}
+
+impl PlayStrategy for Bot {
+  fn name(&self) -> String {
+    self.name.clone()
+  }
+
+  fn bid(&mut self, player: &Player, view: &PlayerView) -> u32 {
+    let mut message = Bot::view("bid", view);
+    message["money"] = json!(player.money);
+    match self.ask(message, "bid") {
+      Some(answer) => match answer.as_u64().and_then(|bid| u32::try_from(bid).ok()) {
+        Some(bid) => bid,
+        None => {
+          self.illegal(&answer);
+          1
+        }
+      },
+      None => 1,
+    }
+  }
+
+  fn hit(&mut self, view: &PlayerView) -> bool {
+    self.action(view, &[Action::Hit, Action::Stand]) == Action::Hit
+  }
+
+  fn action(&mut self, view: &PlayerView, allowed: &[Action]) -> Action {
+    let mut message = Bot::view("action", view);
+    let names: Vec<&str> = allowed.iter().map(|action| action.name()).collect();
+    message["allowed"] = json!(names);
+    let cautious = match allowed.contains(&Action::Stand) {
+      true => Action::Stand,
+      false => allowed.first().copied().unwrap_or(Action::Stand),
+    };
+    let Some(answer) = self.ask(message, "action") else {
+      return cautious;
+    };
+    let action = allowed
+      .iter()
+      .find(|action| answer.as_str() == Some(action.name()));
+    match action {
+      Some(action) => *action,
+      None => {
+        self.illegal(&answer);
+        cautious
+      }
+    }
+  }
+
+  fn redeal(&mut self, view: &PlayerView) -> bool {
+    self.choice("redeal", view, true)
+  }
+
+  fn insurance(&mut self, view: &PlayerView) -> bool {
+    self.choice("insurance", view, false)
+  }
+}
+
+impl Drop for Bot {
+  fn drop(&mut self) {
+    // Closing its input tells the bot the game is over
+    self.stdin.take();
+    if self.child.try_wait().ok().flatten().is_none() {
+      thread::sleep(Duration::from_millis(10));
+      let _ = self.child.kill();
+    }
+    let _ = self.child.wait();
+    let Problems {
+      timeouts,
+      unreadable,
+      illegal,
+      ..
+    } = self.problems;
+    if timeouts + unreadable + illegal > 0 {
+      eprintln!(
+        "Bot '{}': {} late, {} unreadable and {} illegal answers out of {} messages",
+        self.name, timeouts, unreadable, illegal, self.id
+      );
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::strategy::with_view;
+  use crate::Value;
+
+  /// A bot running the shell `script`, saved under `name`. The script
+  /// deletes its file as soon as the shell has it open.
+  fn script_bot(name: &str, script: &str, timeout: Duration) -> Bot {
+    let path = std::env::temp_dir().join(format!("simulatie-21-{name}-{}.sh", std::process::id()));
+    std::fs::write(&path, format!("rm -f \"$0\"\n{script}")).unwrap();
+    Bot::spawn("test", &format!("sh {}", path.display()), timeout).unwrap()
+  }
+
+  /// A bot answering every message with the id and `fields`
+  fn bot(name: &str, fields: &str) -> Bot {
+    let script = format!(
+      "while read line; do\n\
+       id=$(echo \"$line\" | sed 's/.*\"id\":\\([0-9]*\\).*/\\1/')\n\
+       echo \"{{\\\"id\\\":$id,{}}}\"\n\
+       done\n",
+      fields.replace('"', "\\\"")
+    );
+    script_bot(name, &script, TIMEOUT)
+  }
+
+  fn bid(bot: &mut Bot) -> u32 {
+    with_view(&[], None, |view| bot.bid(&Player::new(1), view))
+  }
+
+  fn decide(bot: &mut Bot, allowed: &[Action]) -> Action {
+    let cards = [Value::Ten, Value::Six];
+    with_view(&cards, None, |view| bot.action(view, allowed))
+  }
+
+  #[test]
+  fn plays_what_the_bot_answers() {
+    let mut bot = bot("answers", r#""action":"hit","bid":25"#);
+    assert_eq!(decide(&mut bot, &[Action::Hit, Action::Stand]), Action::Hit);
+    assert_eq!(bid(&mut bot), 25);
+    assert_eq!(bot.problems.illegal, 0);
+  }
+
+  #[test]
+  fn illegal_actions_stand() {
+    let mut bot = bot("illegal", r#""action":"split","bid":-5"#);
+    assert_eq!(
+      decide(&mut bot, &[Action::Hit, Action::Stand]),
+      Action::Stand
+    );
+    assert_eq!(bid(&mut bot), 1);
+    assert_eq!(bot.problems.illegal, 2);
+  }
+
+  #[test]
+  fn late_answers_stand_and_are_passed_over() {
+    let script = "read line; sleep 1; echo '{\"id\":1,\"action\":\"hit\"}'\n\
+                  read line; echo '{\"id\":2,\"action\":\"hit\"}'\n";
+    let mut bot = script_bot("late", script, Duration::from_millis(200));
+    assert_eq!(
+      decide(&mut bot, &[Action::Hit, Action::Stand]),
+      Action::Stand
+    );
+    assert_eq!(bot.problems.timeouts, 1);
+    thread::sleep(Duration::from_secs(1));
+    assert_eq!(decide(&mut bot, &[Action::Hit, Action::Stand]), Action::Hit);
+  }
+
+  #[test]
+  fn a_bot_that_exits_is_no_longer_asked() {
+    let mut bot = script_bot("exits", "exit 0\n", TIMEOUT);
+    assert_eq!(
+      decide(&mut bot, &[Action::Hit, Action::Stand]),
+      Action::Stand
+    );
+    assert!(bot.problems.exited);
+    assert_eq!(bid(&mut bot), 1);
+  }
+}
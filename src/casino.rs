@@ -0,0 +1,140 @@
+//! Casino blackjack. Bids go down before the deal, all player cards are open
+//! and players can double down, split pairs, surrender and insure against a
+//! bank natural.
+
+use std::fmt;
+
+use crate::events::{Event, Seat};
+use crate::rules::{DoubleRule, Rules, Surrender};
+use crate::strategy::{Action, Actions};
+use crate::variant::{SeatHand, Variant};
+use crate::{Game, Player, Visibility};
+
+pub struct Casino;
+
+impl Variant for Casino {
+  fn name(&self) -> &'static str {
+    "Casino blackjack"
+  }
+
+  fn rules(&self) -> Rules {
+    Rules::casino()
+  }
+
+  /// Two open cards for every player with the bank's open card in between.
+  /// The bank only gets its hole card now if it peeks.
+  fn deal(&self, game: &mut Game) -> Result<(), String> {
+    game.place_bids();
+    for round in 0..2 {
+      for player in &mut game.players {
+        if player.bid != 0 {
+          let card = game.deck.deal(&mut player.hand, Visibility::All)?;
+          game
+            .observers
+            .emit(Event::dealt(Seat::player(player.id), card));
+        }
+      }
+      if round == 0 {
+        let card = game.deck.deal(&mut game.bank.hand, Visibility::All)?;
+        game.observers.emit(Event::dealt(Seat::Bank, card));
+      } else if game.rules.peek {
+        let card = game.deck.deal(&mut game.bank.hand, Visibility::Owner)?;
+        game.observers.emit(Event::dealt(Seat::Bank, card));
+      }
+    }
+    Ok(())
+  }
+
+  fn allowed_actions(
+    &self,
+    rules: &Rules,
+    bank: &Player,
+    player: &Player,
+    hands: &[SeatHand],
+    current: usize,
+  ) -> Actions {
+    let seat = &hands[current];
+    if seat.hand.greatest_value() == 21 {
+      return Actions::from([Action::Stand]);
+    }
+    let can_hit = !seat.split_aces || rules.hit_split_aces;
+    let mut allowed = Actions::new();
+    if can_hit {
+      allowed.push(Action::Hit);
+    }
+    allowed.push(Action::Stand);
+    let [first, second] = seat.hand.cards() else {
+      return allowed;
+    };
+
+    let double = match rules.double {
+      DoubleRule::Never => false,
+      DoubleRule::NineToEleven => (9..=11).any(|total| seat.hand.can_make(total)),
+      DoubleRule::Any => true,
+    };
+    if can_hit
+      && double
+      && (!seat.split || rules.double_after_split)
+      && seat.can_raise(rules, bank, player, seat.bid)
+    {
+      allowed.push(Action::Double);
+    }
+
+    if rules.worth.worths(first.value()) == rules.worth.worths(second.value())
+      && hands.len() < rules.split_hands
+      && (!seat.split_aces || rules.resplit_aces)
+      && seat.can_split(rules, bank, player)
+    {
+      allowed.push(Action::Split);
+    }
+
+    // Without a peek nobody knows yet whether the bank's natural takes the
+    // whole bid
+    if rules.surrender == Surrender::Late && rules.peek && hands.len() == 1 {
+      allowed.push(Action::Surrender);
+    }
+    allowed
+  }
+
+  fn fmt_rules(&self, rules: &Rules, f: &mut fmt::Formatter) -> fmt::Result {
+    let yes_no = |flag: bool| if flag { "yes" } else { "no" };
+    if rules.split_hands > 1 {
+      writeln!(
+        f,
+        "Split up to {} hands (resplit Aces: {}, hit split Aces: {})",
+        rules.split_hands,
+        yes_no(rules.resplit_aces),
+        yes_no(rules.hit_split_aces)
+      )?;
+    } else {
+      writeln!(f, "No splitting")?;
+    }
+    let double = match rules.double {
+      DoubleRule::Never => "never",
+      DoubleRule::NineToEleven => "on 9 to 11",
+      DoubleRule::Any => "on any two cards",
+    };
+    writeln!(
+      f,
+      "Double down {} (after split: {})",
+      double,
+      yes_no(rules.double_after_split)
+    )?;
+    let surrender = match rules.surrender {
+      Surrender::Never => "never",
+      Surrender::Late => "late",
+      Surrender::Early => "early",
+    };
+    writeln!(f, "Surrender: {}", surrender)?;
+    writeln!(f, "Insurance: {}", yes_no(rules.insurance))?;
+    let peek = match rules.peek {
+      true => "peeks for a natural",
+      false => "takes no hole card",
+    };
+    let soft_17 = match rules.bank_hits_soft_17 {
+      true => "hits",
+      false => "stands on",
+    };
+    write!(f, "Bank {} and {} soft 17", peek, soft_17)
+  }
+}
@@ -0,0 +1,309 @@
+//! `chart` mode: what a strategy does with each two card hand against every
+//! open bank card, as a colour-coded grid for the terminal, a Markdown table
+//! for the wiki or a standalone HTML page.
+
+use std::fmt::Write;
+
+use console::{style, Color};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::bank_odds::parse_stand_on;
+use crate::rules::Rules;
+use crate::scenario::BANKROLL;
+use crate::strategy::{Action, PlayStrategy, PlayerView};
+use crate::variant::SeatHand;
+use crate::{
+  exit_with, flag_values, parse_play_strategy, parse_rules, Card, Deck, Hand, Player, Suit, Value,
+};
+
+/// Every action with the letter it is charted as, its colour and its name.
+const LEGEND: [(Action, char, Color, &str); 6] = [
+  (Action::Hit, 'H', Color::Red, "hit"),
+  (Action::Stand, 'S', Color::Green, "stand"),
+  (Action::Double, 'D', Color::Blue, "double"),
+  (Action::Split, 'P', Color::Magenta, "split"),
+  (Action::Surrender, 'R', Color::White, "surrender"),
+  (Action::Buy, 'B', Color::Cyan, "buy"),
+];
+
+/// Background colours of the HTML page, in the order of `LEGEND`
+const HTML_COLOURS: [&str; 6] = [
+  "#f4a6a6", "#a6dba0", "#9ecae1", "#d4a6e8", "#e0e0e0", "#9ee3e0",
+];
+
+/// `chart <strategy>` with the usual `--rule` and `--bank stand-on-<n>`
+/// options and `--format terminal|markdown|html`, terminal by default.
+pub fn run(args: &[String]) {
+  let Some(name) = args.get(2) else {
+    exit_with("Usage: chart <strategy>, like: chart basic --format markdown");
+  };
+  let rules = parse_rules(args);
+  let stand_on = parse_stand_on(args);
+  let chart = Chart::of(parse_play_strategy(name).as_mut(), &rules, stand_on);
+  let output = match flag_values(args, "--format").first().copied() {
+    None | Some("terminal") => chart.terminal(),
+    Some("markdown") => chart.markdown(),
+    Some("html") => chart.html(),
+    Some(format) => exit_with(&format!(
+      "Unknown chart format '{format}'\nExpected terminal, markdown or html"
+    )),
+  };
+  print!("{output}");
+}
+
+/// The first action a strategy takes per starting hand and bank card.
+pub struct Chart {
+  pub title: String,
+  /// Bank cards heading the columns
+  pub columns: Vec<String>,
+  /// Starting hands labelling the rows, with the action for each column
+  pub rows: Vec<(String, Vec<Action>)>,
+}
+
+impl Chart {
+  /// Asks `strategy` what it does with every two card hand against every
+  /// bank card of a bank standing on `stand_on`. Cards that count the same
+  /// share a row or column, and hands the variant settles on the deal are
+  /// left out.
+  pub fn of(strategy: &mut dyn PlayStrategy, rules: &Rules, stand_on: u32) -> Chart {
+    let deck = Deck::new(&rules.deck, &rules.worth, StdRng::seed_from_u64(0));
+    let counts = deck.counts();
+    let values: Vec<Value> = (0..14)
+      .filter(|index| counts[*index] > 0)
+      .map(|index| Value::from_u32(index as u32 + 1))
+      .collect();
+
+    let mut bank_cards: Vec<Card> = Vec::new();
+    for value in &values {
+      if !bank_cards
+        .iter()
+        .any(|card| rules.worth.worths(card.value()) == rules.worth.worths(*value))
+      {
+        let mut card = Card::new(Suit::Spades, *value);
+        card.open();
+        bank_cards.push(card);
+      }
+    }
+
+    // Hard totals first, then soft ones, then pairs, each going up
+    let values: Vec<Value> = bank_cards.iter().map(|card| card.value()).collect();
+    let mut hands: Vec<(u32, u32, String, Hand)> = Vec::new();
+    for (index, first) in values.iter().enumerate() {
+      for second in &values[index..] {
+        let mut hand = Hand::new();
+        hand.add_card(Card::new(Suit::Spades, *first), &rules.worth);
+        hand.add_card(Card::new(Suit::Spades, *second), &rules.worth);
+        let seat = SeatHand::new(hand, 1);
+        if rules.variant.settle_early(rules, &seat).is_some() || hand.is_natural() {
+          continue;
+        }
+        let total = hand.greatest_value();
+        let (kind, order, label) = if first == second {
+          let value = first.to_string().trim().to_string();
+          (2, first.to_u32(), format!("{value},{value}"))
+        } else if hand.is_soft() {
+          (1, total, format!("soft {total}"))
+        } else {
+          (0, total, format!("hard {total}"))
+        };
+        if !hands.iter().any(|(_, _, other, _)| *other == label) {
+          hands.push((kind, order, label, hand));
+        }
+      }
+    }
+    hands.sort_by_key(|(kind, order, _, _)| (*kind, *order));
+
+    let mut bank = Player::new(0);
+    bank.money = BANKROLL;
+    let mut player = Player::new(1);
+    player.money = BANKROLL;
+    let rows = hands
+      .into_iter()
+      .map(|(_, _, label, hand)| {
+        let seats = [SeatHand::new(hand, 10)];
+        let allowed = rules
+          .variant
+          .allowed_actions(rules, &bank, &player, &seats, 0);
+        let actions = bank_cards
+          .iter()
+          .map(|card| {
+            let view = PlayerView {
+              hand: &hand,
+              bank_card: Some(card),
+              deck: &deck,
+              rules,
+              bank_stands_on: Some(stand_on),
+            };
+            match strategy.action(&view, &allowed) {
+              action if allowed.contains(&action) => action,
+              _ if allowed.contains(&Action::Stand) => Action::Stand,
+              _ => Action::Hit,
+            }
+          })
+          .collect();
+        (label, actions)
+      })
+      .collect();
+
+    Chart {
+      title: format!("{} ({})", strategy.name(), rules.variant.name()),
+      columns: bank_cards
+        .iter()
+        .map(|card| card.value().to_string().trim().to_string())
+        .collect(),
+      rows,
+    }
+  }
+
+  /// The legend entries of the actions in the chart.
+  fn legend(&self) -> impl Iterator<Item = &(Action, char, Color, &str)> {
+    LEGEND.iter().filter(|(action, ..)| {
+      self
+        .rows
+        .iter()
+        .any(|(_, actions)| actions.contains(action))
+    })
+  }
+
+  pub fn terminal(&self) -> String {
+    let mut output = format!("{}\n\n{:<8}", style(&self.title).bold(), "");
+    for column in &self.columns {
+      write!(output, "{column:^3}").unwrap();
+    }
+    output.push('\n');
+    for (label, actions) in &self.rows {
+      write!(output, "{label:<8}").unwrap();
+      for action in actions {
+        let (_, letter, colour, _) = entry(*action);
+        let cell = style(format!(" {letter} ")).black().bg(*colour);
+        write!(output, "{cell}").unwrap();
+      }
+      output.push('\n');
+    }
+    output.push('\n');
+    for (_, letter, colour, name) in self.legend() {
+      write!(
+        output,
+        "{} {name}  ",
+        style(format!(" {letter} ")).black().bg(*colour)
+      )
+      .unwrap();
+    }
+    output.push('\n');
+    output
+  }
+
+  pub fn markdown(&self) -> String {
+    let mut output = format!("### {}\n\n| |", self.title);
+    for column in &self.columns {
+      write!(output, " {column} |").unwrap();
+    }
+    output.push_str("\n|---|");
+    output.push_str(&":-:|".repeat(self.columns.len()));
+    output.push('\n');
+    for (label, actions) in &self.rows {
+      write!(output, "| {label} |").unwrap();
+      for action in actions {
+        write!(output, " {} |", entry(*action).1).unwrap();
+      }
+      output.push('\n');
+    }
+    let legend: Vec<String> = self
+      .legend()
+      .map(|(_, letter, _, name)| format!("{letter} = {name}"))
+      .collect();
+    writeln!(output, "\n{}", legend.join(", ")).unwrap();
+    output
+  }
+
+  pub fn html(&self) -> String {
+    let mut output = format!(
+      "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n<style>\n\
+       body {{ font-family: sans-serif; }}\n\
+       table {{ border-collapse: collapse; }}\n\
+       th, td {{ padding: 4px 8px; text-align: center; border: 1px solid #fff; }}\n",
+      escape(&self.title)
+    );
+    for (index, (_, letter, _, _)) in LEGEND.iter().enumerate() {
+      writeln!(
+        output,
+        ".{letter} {{ background: {}; }}",
+        HTML_COLOURS[index]
+      )
+      .unwrap();
+    }
+    write!(
+      output,
+      "</style>\n</head>\n<body>\n<h1>{}</h1>\n<table>\n<tr><th></th>",
+      escape(&self.title)
+    )
+    .unwrap();
+    for column in &self.columns {
+      write!(output, "<th>{}</th>", escape(column)).unwrap();
+    }
+    output.push_str("</tr>\n");
+    for (label, actions) in &self.rows {
+      write!(output, "<tr><th>{}</th>", escape(label)).unwrap();
+      for action in actions {
+        let letter = entry(*action).1;
+        write!(output, "<td class=\"{letter}\">{letter}</td>").unwrap();
+      }
+      output.push_str("</tr>\n");
+    }
+    output.push_str("</table>\n<p>");
+    for (_, letter, _, name) in self.legend() {
+      write!(
+        output,
+        "<span class=\"{letter}\">&nbsp;{letter}&nbsp;</span> {name} "
+      )
+      .unwrap();
+    }
+    output.push_str("</p>\n</body>\n</html>\n");
+    output
+  }
+}
+
+fn entry(action: Action) -> &'static (Action, char, Color, &'static str) {
+  LEGEND
+    .iter()
+    .find(|(charted, ..)| *charted == action)
+    .expect("Every action is in the legend")
+}
+
+fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::strategy::BasicStrategy;
+
+  #[test]
+  fn basic_casino_chart_in_markdown() {
+    let mut rules = Rules::default();
+    rules.set("variant", "casino").unwrap();
+    let chart = Chart::of(&mut BasicStrategy, &rules, 17);
+    assert_eq!(
+      chart.columns,
+      ["A", "2", "3", "4", "5", "6", "7", "8", "9", "10"]
+    );
+    // Hard 5 to 19, soft 13 to 20 and ten pairs
+    assert_eq!(chart.rows.len(), 15 + 8 + 10);
+
+    let markdown = chart.markdown();
+    let lines: Vec<&str> = markdown.lines().collect();
+    assert_eq!(lines[0], "### basic (Casino blackjack)");
+    assert_eq!(lines[2], "| | A | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10 |");
+    assert!(lines.contains(&"| hard 11 | H | D | D | D | D | D | D | D | D | D |"));
+    assert!(lines.contains(&"| 8,8 | P | P | P | P | P | P | P | P | P | P |"));
+    assert_eq!(
+      lines.last(),
+      Some(&"H = hit, S = stand, D = double, P = split, R = surrender")
+    );
+  }
+}
@@ -0,0 +1,113 @@
+//! `client` mode: a seat at a `server` game, played from the terminal.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+
+use console::style;
+use dialoguer::{Confirm, Input, Select};
+
+use crate::{exit_with, flag_values};
+
+/// A server on the same machine
+const DEFAULT_ADDRESS: &str = "127.0.0.1:2121";
+
+/// `client` with `--address <host:port>` of the server and `--name <name>`
+/// to join as, asked for when left out. `--watch` only watches the game.
+pub fn run(args: &[String]) {
+  let address = flag_values(args, "--address")
+    .first()
+    .copied()
+    .unwrap_or(DEFAULT_ADDRESS);
+  let name = match flag_values(args, "--name").first() {
+    Some(name) => name.to_string(),
+    None => Input::new()
+      .with_prompt("Your name")
+      .interact_text()
+      .unwrap_or_else(|error| exit_with(&error.to_string())),
+  };
+  let mut writer = TcpStream::connect(address)
+    .unwrap_or_else(|error| exit_with(&format!("Could not connect to {address}: {error}")));
+  let reader = BufReader::new(writer.try_clone().expect("Could not read from the server"));
+  let mut send = |line: String| {
+    writeln!(writer, "{line}")
+      .unwrap_or_else(|error| exit_with(&format!("Lost the server: {error}")))
+  };
+  let watching = args.iter().any(|arg| arg == "--watch");
+  let greeting = if watching { "WATCH" } else { "JOIN" };
+  send(format!(
+    "{greeting} {}",
+    name.replace(char::is_whitespace, "_")
+  ));
+
+  for line in reader.lines() {
+    let Ok(line) = line else {
+      break;
+    };
+    let (kind, rest) = line.split_once(' ').unwrap_or((&line, ""));
+    if watching {
+      match kind {
+        "WATCHING" => println!("{}", style(format!("Watching {rest}")).bold()),
+        "ROUND" => println!("\n{}", style(format!("Round {rest}")).bold()),
+        "REVEAL" | "RESULT" => println!("{}", style(&line).bold()),
+        "BYE" => break,
+        _ => println!("{line}"),
+      }
+      continue;
+    }
+    match kind {
+      "WELCOME" => {
+        let (seat, variant) = rest.split_once(' ').unwrap_or((rest, ""));
+        println!("{}", style(format!("Seat {seat} at {variant}")).bold());
+        println!("Waiting for the other players...");
+      }
+      "ROUND" => println!("\n{}", style(format!("Round {rest}")).bold()),
+      "HAND" if rest.is_empty() => println!("Your hand: no cards yet"),
+      "HAND" => println!("Your hand: {}", style(rest).bold()),
+      "BANK" => println!("Bank:      {rest}"),
+      "TABLE" if !rest.is_empty() => println!("Open cards: {rest}"),
+      "TABLE" => {}
+      "BID?" => {
+        let bid: u32 = Input::new()
+          .with_prompt(format!("Bid (you have {rest})"))
+          .default(10)
+          .interact_text()
+          .unwrap_or_else(|error| exit_with(&error.to_string()));
+        send(format!("BID {bid}"));
+      }
+      "ACTION?" => {
+        let actions: Vec<&str> = rest.split(' ').collect();
+        let choice = Select::new()
+          .with_prompt("Your move")
+          .items(&actions)
+          .default(0)
+          .interact()
+          .unwrap_or_else(|error| exit_with(&error.to_string()));
+        send(format!("ACTION {}", actions[choice]));
+      }
+      "REDEAL?" | "INSURANCE?" => {
+        let prompt = match kind {
+          "REDEAL?" => "Deal this hand again?",
+          _ => "Insure against a bank natural?",
+        };
+        let yes = Confirm::new()
+          .with_prompt(prompt)
+          .interact()
+          .unwrap_or_else(|error| exit_with(&error.to_string()));
+        let answer = if yes { "yes" } else { "no" };
+        send(format!("{} {answer}", kind.trim_end_matches('?')));
+      }
+      "RESULT" => {
+        let (money, change) = rest.split_once(' ').unwrap_or((rest, ""));
+        let change = match change.starts_with('-') {
+          true => style(change).red(),
+          false => style(change).green(),
+        };
+        println!("Result: {change}, you have {money}");
+      }
+      "ERROR" => println!("{}", style(rest).red()),
+      "BYE" => break,
+      _ => println!("{line}"),
+    }
+  }
+  println!("The game is over");
+}
@@ -0,0 +1,82 @@
+//! `compare` mode: plays the same seeded rounds with each strategy alone at
+//! the table, and shows what it wins or loses per 1$ bid and what it gains
+//! over the first strategy. Strategies get the same cards for as long as
+//! they play alike, so the gains are measured much more precisely than the
+//! EVs themselves.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::rules::Rules;
+use crate::scenario::{parse_rounds, Estimate, BANKROLL};
+use crate::strategy::PlayStrategy;
+use crate::{
+  check_play_strategy, exit_with, flag_values, parse_bank_strategy, parse_play_strategy,
+  parse_rules, Game, Player,
+};
+
+const DEFAULT_ROUNDS: u32 = 100_000;
+const DEFAULT_STRATEGIES: [&str; 3] = ["optimal", "basic", "composition"];
+const SEED: u64 = 21;
+
+/// `compare` with the usual `--rule`, `--bank` and `--player` options and
+/// `--rounds <n>`.
+pub fn run(args: &[String]) {
+  let rounds = parse_rounds(args, DEFAULT_ROUNDS);
+  let mut strategies = flag_values(args, "--player");
+  if strategies.is_empty() {
+    strategies = DEFAULT_STRATEGIES.to_vec();
+  }
+  for name in &strategies {
+    check_play_strategy(name);
+  }
+  let rules = parse_rules(args);
+  // Every strategy plays alone at the table
+  rules
+    .check_deck(1)
+    .unwrap_or_else(|error| exit_with(&error));
+
+  println!("{} rounds of {} per strategy", rounds, rules.variant.name());
+  println!("EV per 1$ of starting bid, give or take one standard error");
+  let mut reference: Option<Vec<f64>> = None;
+  for name in strategies {
+    let results = play(args, &rules, parse_play_strategy(name), rounds);
+    let estimate = Estimate::of(results.iter().copied());
+    let Some(reference) = &reference else {
+      println!("  {:<14} {}", name, estimate);
+      reference = Some(results);
+      continue;
+    };
+    let gains = results
+      .iter()
+      .zip(reference)
+      .map(|(result, base)| result - base);
+    let gain = Estimate::of(gains);
+    println!(
+      "  {:<14} {}, gain {:>+8.4} ± {:.4}",
+      name, estimate, gain.mean, gain.error
+    );
+  }
+}
+
+/// Result of every round per 1$ bid, for a player using `strategy`.
+fn play(args: &[String], rules: &Rules, strategy: Box<dyn PlayStrategy>, rounds: u32) -> Vec<f64> {
+  let mut bank = Player::new(0);
+  bank.money = BANKROLL;
+  let mut game = Game::new(
+    bank,
+    parse_bank_strategy(args),
+    rules.clone(),
+    StdRng::seed_from_u64(SEED),
+  );
+  game.add_player(Player::new(1), strategy);
+  let bar = indicatif::ProgressBar::new(rounds as u64);
+  let results = (0..rounds)
+    .map(|_| {
+      bar.inc(1);
+      game.play_alone()
+    })
+    .collect();
+  bar.finish_and_clear();
+  results
+}
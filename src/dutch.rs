@@ -0,0 +1,127 @@
+//! Dutch "eenentwintigen". Every player gets an open card and bids on it
+//! before the closed card comes. 21 and charlies win on the spot, and unlucky
+//! starting hands can be dealt again.
+
+use std::fmt;
+
+use crate::events::{Event, Seat};
+use crate::rules::{Outcome, Rules};
+use crate::strategy::{Action, Actions, PlayerView};
+use crate::variant::{open_bank_card, SeatHand, Variant};
+use crate::{Game, Player, Visibility};
+
+pub struct Dutch;
+
+/// Redeals a hand gets at most, so triggers matching every starting hand
+/// can't deal forever. The last hand dealt is played as it is.
+const MAX_REDEALS: usize = 10;
+
+impl Variant for Dutch {
+  fn name(&self) -> &'static str {
+    "Dutch eenentwintigen"
+  }
+
+  fn rules(&self) -> Rules {
+    Rules::default()
+  }
+
+  fn deal(&self, game: &mut Game) -> Result<(), String> {
+    // Deal open card
+    for player in &mut game.players {
+      let card = game.deck.deal(&mut player.hand, Visibility::All)?;
+      game
+        .observers
+        .emit(Event::dealt(Seat::player(player.id), card));
+    }
+    let card = game.deck.deal(&mut game.bank.hand, Visibility::All)?;
+    game.observers.emit(Event::dealt(Seat::Bank, card));
+
+    game.place_bids();
+
+    // Deal closed cards
+    for player in &mut game.players {
+      let card = game.deck.deal(&mut player.hand, Visibility::Owner)?;
+      game
+        .observers
+        .emit(Event::dealt(Seat::player(player.id), card));
+
+      for _ in 0..MAX_REDEALS {
+        let Some(trigger) = game.rules.redeal_trigger(&player.hand) else {
+          break;
+        };
+        let strategy = game.strategies.get_mut(&player.id).unwrap();
+        let view = PlayerView {
+          hand: &player.hand,
+          bank_card: open_bank_card(&game.bank),
+          deck: &game.deck,
+          rules: &game.rules,
+          bank_stands_on: game.bank_strategy.stands_on(),
+        };
+        let taken = !game.rules.redeal_optional || strategy.redeal(&view);
+        game.observers.emit(Event::Redeal {
+          player: player.id,
+          trigger: *trigger,
+          taken,
+        });
+        if !taken {
+          break;
+        }
+
+        game.deck.discard(&mut player.hand);
+        for visibility in [Visibility::All, Visibility::Owner] {
+          let card = game.deck.deal(&mut player.hand, visibility)?;
+          game
+            .observers
+            .emit(Event::dealt(Seat::player(player.id), card));
+        }
+      }
+    }
+
+    let card = game.deck.deal(&mut game.bank.hand, Visibility::None)?;
+    game.observers.emit(Event::dealt(Seat::Bank, card));
+    Ok(())
+  }
+
+  fn settle_early(&self, rules: &Rules, seat: &SeatHand) -> Option<Outcome> {
+    let hand = &seat.hand;
+    if hand.is_bust() {
+      Some(Outcome::Bust)
+    } else if hand.is_charlie(rules.charlie_cards) {
+      Some(Outcome::Charlie)
+    } else if hand.is_natural() {
+      Some(Outcome::Natural)
+    } else if hand.best_total() == Some(21) {
+      Some(Outcome::Made21)
+    } else {
+      None
+    }
+  }
+
+  fn allowed_actions(
+    &self,
+    _rules: &Rules,
+    _bank: &Player,
+    _player: &Player,
+    _hands: &[SeatHand],
+    _current: usize,
+  ) -> Actions {
+    Actions::from([Action::Hit, Action::Stand])
+  }
+
+  fn fmt_rules(&self, rules: &Rules, f: &mut fmt::Formatter) -> fmt::Result {
+    if rules.redeals.is_empty() {
+      return write!(f, "No redeals");
+    }
+    let redeals = rules
+      .redeals
+      .iter()
+      .map(|trigger| trigger.to_string())
+      .collect::<Vec<_>>()
+      .join(", ");
+    let choice = match rules.redeal_optional {
+      true => "player's choice",
+      false => "forced",
+    };
+    write!(f, "Redeal on {} ({})", redeals, choice)
+  }
+}
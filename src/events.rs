@@ -0,0 +1,329 @@
+//! What happens during a round, step by step. `Game` tells every registered
+//! observer about each step as it happens, so logs, statistics and user
+//! interfaces can follow a game without the engine knowing about them.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::rules::{Outcome, RedealTrigger};
+use crate::{Card, Hand, Value, Visibility};
+
+/// Whose hand a card goes to.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Seat {
+  Bank,
+  /// A player's hand, numbered from 0 and only above 0 after splitting
+  Player {
+    id: u32,
+    hand: usize,
+  },
+}
+
+impl Seat {
+  /// The first hand of player `id`.
+  pub fn player(id: u32) -> Seat {
+    Seat::Player { id, hand: 0 }
+  }
+}
+
+/// A step of a round.
+#[derive(Clone, Copy)]
+pub enum Event {
+  /// Rounds are numbered from 1 over the whole game
+  RoundStarted {
+    round: u32,
+  },
+  BidPlaced {
+    player: u32,
+    bid: u32,
+  },
+  CardDealt {
+    to: Seat,
+    card: Card,
+    visibility: Visibility,
+  },
+  /// The player was entitled to a new starting hand and took it or not
+  Redeal {
+    player: u32,
+    trigger: RedealTrigger,
+    taken: bool,
+  },
+  PlayerBust {
+    player: u32,
+    hand: usize,
+    total: u32,
+  },
+  PlayerStood {
+    player: u32,
+    hand: usize,
+    total: u32,
+  },
+  CharlieWin {
+    player: u32,
+    hand: usize,
+    cards: usize,
+  },
+  /// The bank's hand is final
+  BankResolved {
+    total: u32,
+    bust: bool,
+    natural: bool,
+  },
+  /// Insurance against a bank natural was paid out or lost
+  Insurance {
+    player: u32,
+    stake: u32,
+    net: i64,
+  },
+  /// A hand was settled, with what the player won or lost on it
+  Payout {
+    player: u32,
+    hand: usize,
+    /// The first two cards of the hand, in the order they were dealt
+    start: (Value, Value),
+    cards: usize,
+    outcome: Outcome,
+    bid: u32,
+    net: i64,
+  },
+  /// The bank could no longer cover a bid after this many rounds and was
+  /// replaced by a fresh one
+  BankBroke {
+    rounds: u32,
+  },
+}
+
+impl Event {
+  /// `card` dealt to `to`, as visible as it was dealt.
+  pub fn dealt(to: Seat, card: Card) -> Event {
+    Event::CardDealt {
+      to,
+      card,
+      visibility: card.visibility,
+    }
+  }
+
+  /// The bank ending on `hand`.
+  pub fn bank_resolved(hand: &Hand) -> Event {
+    Event::BankResolved {
+      total: hand.greatest_value(),
+      bust: hand.is_bust(),
+      natural: hand.is_natural(),
+    }
+  }
+}
+
+/// Something following a game.
+pub trait Observer {
+  fn observe(&mut self, event: &Event);
+}
+
+/// An observer shared with whoever reads what it gathered.
+impl<T: Observer> Observer for Rc<RefCell<T>> {
+  fn observe(&mut self, event: &Event) {
+    self.borrow_mut().observe(event);
+  }
+}
+
+/// The observers of a game.
+#[derive(Default)]
+pub struct Observers(Vec<Box<dyn Observer>>);
+
+impl Observers {
+  pub fn add(&mut self, observer: Box<dyn Observer>) {
+    self.0.push(observer);
+  }
+
+  pub fn emit(&mut self, event: Event) {
+    for observer in &mut self.0 {
+      observer.observe(&event);
+    }
+  }
+}
+
+/// Prints every event of the first rounds of a game, one per line.
+pub struct EventLog {
+  pub rounds: u32,
+}
+
+impl Observer for EventLog {
+  fn observe(&mut self, event: &Event) {
+    if let Event::RoundStarted { round } = event {
+      if *round > self.rounds {
+        self.rounds = 0;
+      }
+    }
+    if self.rounds > 0 {
+      println!("{event}");
+    }
+  }
+}
+
+impl fmt::Display for Seat {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Seat::Bank => write!(f, "bank"),
+      Seat::Player { id, hand } => write!(f, "player {id} hand {}", hand + 1),
+    }
+  }
+}
+
+impl fmt::Display for Event {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      Event::RoundStarted { round } => write!(f, "Round {round}"),
+      Event::BidPlaced { player, bid } => write!(f, "  player {player} bids {bid}$"),
+      Event::CardDealt {
+        to,
+        card,
+        visibility,
+      } => {
+        let face = match visibility {
+          Visibility::All => "open",
+          _ => "closed",
+        };
+        let card = format!("{}{}", card.value(), card.suit());
+        write!(f, "  {to} gets {} {face}", card.trim())
+      }
+      Event::Redeal {
+        player,
+        trigger,
+        taken,
+      } => {
+        let choice = if taken { "takes" } else { "declines" };
+        write!(f, "  player {player} {choice} a redeal on {trigger}")
+      }
+      Event::PlayerBust {
+        player,
+        hand,
+        total,
+      } => write!(f, "  player {player} hand {} busts on {total}", hand + 1),
+      Event::PlayerStood {
+        player,
+        hand,
+        total,
+      } => write!(f, "  player {player} hand {} stands on {total}", hand + 1),
+      Event::CharlieWin {
+        player,
+        hand,
+        cards,
+      } => write!(
+        f,
+        "  player {player} hand {} wins with a {cards} card charlie",
+        hand + 1
+      ),
+      Event::BankResolved { natural: true, .. } => write!(f, "  bank has a natural"),
+      Event::BankResolved {
+        bust: true, total, ..
+      } => write!(f, "  bank busts on {total}"),
+      Event::BankResolved { total, .. } => write!(f, "  bank ends on {total}"),
+      Event::Insurance { player, stake, net } => {
+        write!(f, "  player {player} insurance on {stake}$, {net:+}$")
+      }
+      Event::Payout {
+        player,
+        hand,
+        outcome,
+        bid,
+        net,
+        ..
+      } => write!(
+        f,
+        "  player {player} hand {}: {outcome} on {bid}$, {net:+}$",
+        hand + 1
+      ),
+      Event::BankBroke { rounds } => write!(f, "  bank went broke after {rounds} rounds"),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::rules::Rules;
+  use crate::strategy::{play_strategy_by_name, StandOn};
+  use crate::{Game, Player, Value};
+  use rand::rngs::StdRng;
+  use rand::SeedableRng;
+  use std::cell::RefCell;
+  use std::rc::Rc;
+
+  /// Keeps the log lines of every event
+  struct Recorder(Rc<RefCell<Vec<String>>>);
+
+  impl Observer for Recorder {
+    fn observe(&mut self, event: &Event) {
+      self
+        .0
+        .borrow_mut()
+        .push(event.to_string().trim().to_string());
+    }
+  }
+
+  #[test]
+  fn a_round_step_by_step() {
+    let mut game = Game::new(
+      Player::new(0),
+      Box::new(StandOn::new(17)),
+      Rules::default(),
+      StdRng::seed_from_u64(21),
+    );
+    game.add_player(Player::new(1), play_strategy_by_name("fixed-0").unwrap());
+    let events = Rc::new(RefCell::new(Vec::new()));
+    game.add_observer(Box::new(Recorder(events.clone())));
+    // Player open, bank open, player closed, the redeal, bank closed and
+    // five hits
+    use Value::*;
+    game
+      .stack(vec![
+        Seven, Ten, Eight, Two, Two, Seven, Two, Three, Three, Ace, Two,
+      ])
+      .unwrap();
+    game.play_round().unwrap();
+
+    let lines: Vec<String> = events
+      .borrow()
+      .iter()
+      .map(|line| line.replace(['♠', '♥', '♦', '♣'], ""))
+      .collect();
+    assert_eq!(
+      lines,
+      [
+        "Round 1",
+        "player 1 hand 1 gets 7 open",
+        "bank gets 10 open",
+        "player 1 bids 1$",
+        "player 1 hand 1 gets 8 closed",
+        "player 1 takes a redeal on 7+8",
+        "player 1 hand 1 gets 2 open",
+        "player 1 hand 1 gets 2 closed",
+        "bank gets 7 closed",
+        "player 1 hand 1 gets 2 open",
+        "player 1 hand 1 gets 3 open",
+        "player 1 hand 1 gets 3 open",
+        "player 1 hand 1 gets A open",
+        "player 1 hand 1 gets 2 open",
+        "player 1 hand 1 wins with a 7 card charlie",
+        "player 1 hand 1: charlie on 1$, +1$",
+        "bank ends on 17",
+      ]
+    );
+  }
+
+  #[test]
+  fn a_bank_without_money_goes_broke() {
+    let mut bank = Player::new(0);
+    bank.money = 0;
+    let mut game = Game::new(
+      bank,
+      Box::new(StandOn::new(17)),
+      Rules::default(),
+      StdRng::seed_from_u64(21),
+    );
+    let events = Rc::new(RefCell::new(Vec::new()));
+    game.add_observer(Box::new(Recorder(events.clone())));
+    game.clean_up();
+    assert_eq!(*events.borrow(), ["bank went broke after 0 rounds"]);
+  }
+}
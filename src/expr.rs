@@ -0,0 +1,617 @@
+//! Strategies written as a few rules in a small expression language, like
+//! `double if cards == 2 and total == 11; hit if total < 15 or soft and
+//! total < 18`. Each rule is an action (`hit`, `stand`, `double`, `split`,
+//! `surrender` or `buy`), optionally followed by `if` and a condition. The
+//! first allowed action whose condition holds is taken, standing if none
+//! does. `bid <amount> if <condition>` rules decide the bid the same way, 1
+//! if none applies. Rules are separated by `;` or new lines.
+//!
+//! Conditions and amounts combine numbers, `true`, `false` and these values
+//! with `+ - * /`, comparisons (`< <= > >= == !=`), `and`, `or`, `not` and
+//! parentheses:
+//! - `total`: the best total of the hand, `low` its lowest
+//! - `soft`: whether the hand can count an Ace both ways
+//! - `cards`: the number of cards in the hand
+//! - `bank_open`: the most the bank's open card can count as, 0 without one
+//! - `deck_left`: the number of cards left in the deck
+//! - `deck_expected`: the average worth of those cards
+//! - `money`: the player's money, only when bidding
+//!
+//! Bids see the cards dealt before the bids, so in a casino game the hand
+//! is still empty and `total` is 0.
+
+use std::fmt;
+
+use crate::strategy::{Action, PlayStrategy, PlayerView};
+use crate::Player;
+
+/// A play strategy following rules in the expression language.
+pub struct ExprStrategy {
+  name: String,
+  plays: Vec<(Action, Expr)>,
+  bids: Vec<(Expr, Expr)>,
+}
+
+impl ExprStrategy {
+  /// Parses the rules in `program`, with errors starting with the line and
+  /// column they were found at, like `1:9: Unknown value 'tota'`.
+  pub fn parse(name: &str, program: &str) -> Result<ExprStrategy, String> {
+    let tokens = tokenize(program)?;
+    let mut parser = Parser { tokens, next: 0 };
+    let mut strategy = ExprStrategy {
+      name: name.to_string(),
+      plays: Vec::new(),
+      bids: Vec::new(),
+    };
+    while !parser.at_end() {
+      if parser.eat(&Kind::Separator) {
+        continue;
+      }
+      parser.rule(&mut strategy)?;
+      if !parser.at_end() && !parser.eat(&Kind::Separator) {
+        return Err(parser.error("Expected ';' or a new line after the rule"));
+      }
+    }
+    if strategy.plays.is_empty() && strategy.bids.is_empty() {
+      return Err("1:1: Expected at least one rule, like: hit if total < 17".to_string());
+    }
+    Ok(strategy)
+  }
+}
+
+impl PlayStrategy for ExprStrategy {
+  fn name(&self) -> String {
+    self.name.clone()
+  }
+
+  fn bid(&mut self, player: &Player, view: &PlayerView) -> u32 {
+    let mut values = values(view);
+    values[Variable::Money as usize] = player.money as f64;
+    self
+      .bids
+      .iter()
+      .find(|(_, condition)| condition.truth(&values))
+      .map_or(1, |(amount, _)| {
+        amount.number(&values).max(0.0).round() as u32
+      })
+  }
+
+  fn hit(&mut self, view: &PlayerView) -> bool {
+    self.action(view, &[Action::Hit, Action::Stand]) == Action::Hit
+  }
+
+  fn action(&mut self, view: &PlayerView, allowed: &[Action]) -> Action {
+    let values = values(view);
+    self
+      .plays
+      .iter()
+      .find(|(action, condition)| allowed.contains(action) && condition.truth(&values))
+      .map_or(Action::Stand, |(action, _)| *action)
+  }
+}
+
+/// Every value the player can see, with `money` left at 0.
+fn values(view: &PlayerView) -> [f64; VARIABLES.len()] {
+  let hand = view.hand;
+  let mut values = [0.0; VARIABLES.len()];
+  values[Variable::Total as usize] = hand.greatest_value() as f64;
+  values[Variable::Low as usize] = hand.smallest_value() as f64;
+  values[Variable::Soft as usize] = hand.is_soft() as u32 as f64;
+  values[Variable::Cards as usize] = hand.cards().len() as f64;
+  values[Variable::BankOpen as usize] = view.bank_card.map_or(0.0, |card| {
+    let worths = view.rules.worth.worths(card.value());
+    (31 - worths.leading_zeros()) as f64
+  });
+  values[Variable::DeckLeft as usize] = view.deck.cards.len() as f64;
+  values[Variable::DeckExpected as usize] = view.deck.expected_value();
+  values
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Variable {
+  Total,
+  Low,
+  Soft,
+  Cards,
+  BankOpen,
+  DeckLeft,
+  DeckExpected,
+  Money,
+}
+
+/// Every value by name, with whether it is a true or false one
+const VARIABLES: [(&str, Variable, bool); 8] = [
+  ("total", Variable::Total, false),
+  ("low", Variable::Low, false),
+  ("soft", Variable::Soft, true),
+  ("cards", Variable::Cards, false),
+  ("bank_open", Variable::BankOpen, false),
+  ("deck_left", Variable::DeckLeft, false),
+  ("deck_expected", Variable::DeckExpected, false),
+  ("money", Variable::Money, false),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Operator {
+  Add,
+  Subtract,
+  Multiply,
+  Divide,
+  Less,
+  LessOrEqual,
+  Greater,
+  GreaterOrEqual,
+  Equal,
+  NotEqual,
+  And,
+  Or,
+}
+
+enum Expr {
+  Number(f64),
+  Bool(bool),
+  Variable(Variable),
+  Negate(Box<Expr>),
+  Not(Box<Expr>),
+  Binary(Operator, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+  fn number(&self, values: &[f64]) -> f64 {
+    match self {
+      Expr::Number(number) => *number,
+      Expr::Variable(variable) => values[*variable as usize],
+      Expr::Negate(expr) => -expr.number(values),
+      Expr::Binary(operator, left, right) => {
+        let (left, right) = (left.number(values), right.number(values));
+        match operator {
+          Operator::Add => left + right,
+          Operator::Subtract => left - right,
+          Operator::Multiply => left * right,
+          Operator::Divide => left / right,
+          _ => unreachable!("Comparisons are checked to be conditions"),
+        }
+      }
+      Expr::Bool(_) | Expr::Not(_) => unreachable!("Conditions are checked not to be numbers"),
+    }
+  }
+
+  fn truth(&self, values: &[f64]) -> bool {
+    match self {
+      Expr::Bool(truth) => *truth,
+      Expr::Variable(variable) => values[*variable as usize] != 0.0,
+      Expr::Not(expr) => !expr.truth(values),
+      Expr::Binary(Operator::And, left, right) => left.truth(values) && right.truth(values),
+      Expr::Binary(Operator::Or, left, right) => left.truth(values) || right.truth(values),
+      Expr::Binary(operator, left, right) => {
+        let (left, right) = (left.number(values), right.number(values));
+        match operator {
+          Operator::Less => left < right,
+          Operator::LessOrEqual => left <= right,
+          Operator::Greater => left > right,
+          Operator::GreaterOrEqual => left >= right,
+          Operator::Equal => left == right,
+          Operator::NotEqual => left != right,
+          _ => unreachable!("Sums are checked to be numbers"),
+        }
+      }
+      Expr::Number(_) | Expr::Negate(_) => unreachable!("Numbers are checked not to be conditions"),
+    }
+  }
+}
+
+/// Whether an expression is a number or a condition.
+#[derive(Clone, Copy, PartialEq)]
+enum Type {
+  Number,
+  Condition,
+}
+
+impl fmt::Display for Type {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Type::Number => write!(f, "a number"),
+      Type::Condition => write!(f, "a condition"),
+    }
+  }
+}
+
+#[derive(PartialEq)]
+enum Kind {
+  Number(f64),
+  Word(String),
+  Symbol(&'static str),
+  Separator,
+}
+
+struct Token {
+  kind: Kind,
+  line: usize,
+  column: usize,
+}
+
+const SYMBOLS: [&str; 12] = [
+  "<=", ">=", "==", "!=", "<", ">", "+", "-", "*", "/", "(", ")",
+];
+
+fn tokenize(program: &str) -> Result<Vec<Token>, String> {
+  let mut tokens = Vec::new();
+  for (index, line) in program.lines().enumerate() {
+    let mut rest = line;
+    loop {
+      let trimmed = rest.trim_start();
+      let column = line.len() - trimmed.len() + 1;
+      let Some(first) = trimmed.chars().next() else {
+        break;
+      };
+      let (kind, length) = if first == ';' {
+        (Kind::Separator, 1)
+      } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| trimmed.starts_with(**symbol)) {
+        (Kind::Symbol(symbol), symbol.len())
+      } else if first.is_ascii_digit() {
+        let length = trimmed
+          .find(|c: char| !c.is_ascii_digit() && c != '.')
+          .unwrap_or(trimmed.len());
+        let number = trimmed[..length].parse().map_err(|_| {
+          format!(
+            "{}:{column}: Invalid number '{}'",
+            index + 1,
+            &trimmed[..length]
+          )
+        })?;
+        (Kind::Number(number), length)
+      } else if first.is_ascii_alphabetic() || first == '_' {
+        let length = trimmed
+          .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+          .unwrap_or(trimmed.len());
+        (Kind::Word(trimmed[..length].to_string()), length)
+      } else {
+        return Err(format!("{}:{column}: Unexpected '{first}'", index + 1));
+      };
+      tokens.push(Token {
+        kind,
+        line: index + 1,
+        column,
+      });
+      rest = &trimmed[length..];
+    }
+    tokens.push(Token {
+      kind: Kind::Separator,
+      line: index + 1,
+      column: line.len() + 1,
+    });
+  }
+  Ok(tokens)
+}
+
+/// An expression with its type, or where parsing it went wrong
+type Parsed = Result<(Expr, Type), String>;
+
+struct Parser {
+  tokens: Vec<Token>,
+  next: usize,
+}
+
+impl Parser {
+  fn at_end(&self) -> bool {
+    self.next == self.tokens.len()
+  }
+
+  fn peek(&self) -> Option<&Kind> {
+    self.tokens.get(self.next).map(|token| &token.kind)
+  }
+
+  fn eat(&mut self, kind: &Kind) -> bool {
+    let found = self.peek() == Some(kind);
+    self.next += found as usize;
+    found
+  }
+
+  fn eat_word(&mut self, word: &str) -> bool {
+    self.eat(&Kind::Word(word.to_string()))
+  }
+
+  fn error(&self, message: &str) -> String {
+    let (line, column) = match self.tokens.get(self.next).or(self.tokens.last()) {
+      Some(token) => (token.line, token.column),
+      None => (1, 1),
+    };
+    format!("{line}:{column}: {message}")
+  }
+
+  /// `<action> [if <condition>]` or `bid <amount> [if <condition>]`.
+  fn rule(&mut self, strategy: &mut ExprStrategy) -> Result<(), String> {
+    if self.eat_word("bid") {
+      let amount = self.expect(Type::Number, true)?;
+      let condition = self.condition(true)?;
+      strategy.bids.push((amount, condition));
+      return Ok(());
+    }
+    let action = match self.peek() {
+      Some(Kind::Word(word)) => Action::ALL.iter().find(|action| action.name() == word),
+      _ => None,
+    };
+    let Some(action) = action else {
+      return Err(
+        self
+          .error("Expected a rule starting with hit, stand, double, split, surrender, buy or bid"),
+      );
+    };
+    self.next += 1;
+    let condition = self.condition(false)?;
+    strategy.plays.push((*action, condition));
+    Ok(())
+  }
+
+  /// An optional `if <condition>`, always true without one.
+  fn condition(&mut self, bidding: bool) -> Result<Expr, String> {
+    match self.eat_word("if") {
+      true => self.expect(Type::Condition, bidding),
+      false => Ok(Expr::Bool(true)),
+    }
+  }
+
+  fn expect(&mut self, expected: Type, bidding: bool) -> Result<Expr, String> {
+    let start = self.next;
+    let (expr, found) = self.or(bidding)?;
+    if found != expected {
+      self.next = start;
+      return Err(self.error(&format!("Expected {expected}, found {found}")));
+    }
+    Ok(expr)
+  }
+
+  /// Parses operands joined by any of `operators`, all of type `operands`.
+  fn binary(
+    &mut self,
+    bidding: bool,
+    operators: &[(Kind, Operator)],
+    operands: Type,
+    result: Type,
+    operand: fn(&mut Parser, bool) -> Parsed,
+  ) -> Parsed {
+    let start = self.next;
+    let (mut expr, mut found) = operand(self, bidding)?;
+    loop {
+      let Some((_, operator)) = operators.iter().find(|(kind, _)| self.peek() == Some(kind)) else {
+        return Ok((expr, found));
+      };
+      let at = self.next;
+      self.next += 1;
+      let right_start = self.next;
+      let (right, right_type) = operand(self, bidding)?;
+      for (position, found) in [(start, found), (right_start, right_type)] {
+        if found != operands {
+          self.next = position;
+          let message = format!(
+            "Expected {operands} around '{}', found {found}",
+            self.symbol(at)
+          );
+          return Err(self.error(&message));
+        }
+      }
+      expr = Expr::Binary(*operator, Box::new(expr), Box::new(right));
+      found = result;
+    }
+  }
+
+  fn symbol(&self, at: usize) -> String {
+    match &self.tokens[at].kind {
+      Kind::Symbol(symbol) => symbol.to_string(),
+      Kind::Word(word) => word.clone(),
+      _ => String::new(),
+    }
+  }
+
+  fn or(&mut self, bidding: bool) -> Parsed {
+    let operators = [(Kind::Word("or".to_string()), Operator::Or)];
+    self.binary(
+      bidding,
+      &operators,
+      Type::Condition,
+      Type::Condition,
+      Parser::and,
+    )
+  }
+
+  fn and(&mut self, bidding: bool) -> Parsed {
+    let operators = [(Kind::Word("and".to_string()), Operator::And)];
+    self.binary(
+      bidding,
+      &operators,
+      Type::Condition,
+      Type::Condition,
+      Parser::not,
+    )
+  }
+
+  fn not(&mut self, bidding: bool) -> Parsed {
+    if !self.eat_word("not") {
+      return self.comparison(bidding);
+    }
+    let start = self.next;
+    let (expr, found) = self.not(bidding)?;
+    if found != Type::Condition {
+      self.next = start;
+      return Err(self.error(&format!("Expected a condition after 'not', found {found}")));
+    }
+    Ok((Expr::Not(Box::new(expr)), Type::Condition))
+  }
+
+  fn comparison(&mut self, bidding: bool) -> Parsed {
+    let operators = [
+      (Kind::Symbol("<"), Operator::Less),
+      (Kind::Symbol("<="), Operator::LessOrEqual),
+      (Kind::Symbol(">"), Operator::Greater),
+      (Kind::Symbol(">="), Operator::GreaterOrEqual),
+      (Kind::Symbol("=="), Operator::Equal),
+      (Kind::Symbol("!="), Operator::NotEqual),
+    ];
+    self.binary(
+      bidding,
+      &operators,
+      Type::Number,
+      Type::Condition,
+      Parser::sum,
+    )
+  }
+
+  fn sum(&mut self, bidding: bool) -> Parsed {
+    let operators = [
+      (Kind::Symbol("+"), Operator::Add),
+      (Kind::Symbol("-"), Operator::Subtract),
+    ];
+    self.binary(
+      bidding,
+      &operators,
+      Type::Number,
+      Type::Number,
+      Parser::product,
+    )
+  }
+
+  fn product(&mut self, bidding: bool) -> Parsed {
+    let operators = [
+      (Kind::Symbol("*"), Operator::Multiply),
+      (Kind::Symbol("/"), Operator::Divide),
+    ];
+    self.binary(
+      bidding,
+      &operators,
+      Type::Number,
+      Type::Number,
+      Parser::unary,
+    )
+  }
+
+  fn unary(&mut self, bidding: bool) -> Parsed {
+    if !self.eat(&Kind::Symbol("-")) {
+      return self.atom(bidding);
+    }
+    let start = self.next;
+    let (expr, found) = self.unary(bidding)?;
+    if found != Type::Number {
+      self.next = start;
+      return Err(self.error(&format!("Expected a number after '-', found {found}")));
+    }
+    Ok((Expr::Negate(Box::new(expr)), Type::Number))
+  }
+
+  fn atom(&mut self, bidding: bool) -> Parsed {
+    let atom = match self.peek() {
+      Some(Kind::Number(number)) => (Expr::Number(*number), Type::Number),
+      Some(Kind::Symbol("(")) => {
+        self.next += 1;
+        let atom = self.or(bidding)?;
+        if !self.eat(&Kind::Symbol(")")) {
+          return Err(self.error("Expected ')'"));
+        }
+        return Ok(atom);
+      }
+      Some(Kind::Word(word)) if word == "true" || word == "false" => {
+        (Expr::Bool(word == "true"), Type::Condition)
+      }
+      Some(Kind::Word(word)) => {
+        let Some((_, variable, truth)) = VARIABLES.iter().find(|(name, ..)| name == word) else {
+          return Err(self.error(&format!("Unknown value '{word}'")));
+        };
+        if !bidding && *variable == Variable::Money {
+          return Err(self.error("'money' is only known when bidding"));
+        }
+        let found = match truth {
+          true => Type::Condition,
+          false => Type::Number,
+        };
+        (Expr::Variable(*variable), found)
+      }
+      _ => return Err(self.error("Expected a number, a value or '('")),
+    };
+    self.next += 1;
+    Ok(atom)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::strategy::with_view;
+  use crate::Value;
+
+  fn decide(
+    strategy: &mut ExprStrategy,
+    cards: &[Value],
+    bank: Value,
+    allowed: &[Action],
+  ) -> Action {
+    with_view(cards, Some(bank), |view| strategy.action(view, allowed))
+  }
+
+  #[test]
+  fn follows_the_first_rule_that_holds() {
+    use Action::*;
+    use Value::*;
+    let program = "double if cards == 2 and total == 11\n\
+                   hit if total < 15 or (soft and total < 18) or bank_open >= 7";
+    let mut strategy = ExprStrategy::parse("test", program).unwrap();
+    let all = [Hit, Stand, Double];
+    assert_eq!(decide(&mut strategy, &[Nine, Two], Six, &all), Double);
+    assert_eq!(decide(&mut strategy, &[Nine, Two], Six, &[Hit, Stand]), Hit);
+    assert_eq!(decide(&mut strategy, &[Ten, Six], Six, &all), Stand);
+    assert_eq!(decide(&mut strategy, &[Ten, Six], Ace, &all), Hit);
+    assert_eq!(decide(&mut strategy, &[Ace, Six], Two, &all), Hit);
+    assert_eq!(decide(&mut strategy, &[Ten, Nine], Ace, &all), Hit);
+  }
+
+  #[test]
+  fn bids_by_money() {
+    let program = "bid money / 10 if money >= 200; bid 5 if not money > 50; hit if total < 17";
+    let mut strategy = ExprStrategy::parse("test", program).unwrap();
+    let mut player = Player::new(1);
+    assert_eq!(with_view(&[], None, |view| strategy.bid(&player, view)), 1);
+    player.money = 250;
+    assert_eq!(with_view(&[], None, |view| strategy.bid(&player, view)), 25);
+    player.money = 40;
+    assert_eq!(with_view(&[], None, |view| strategy.bid(&player, view)), 5);
+  }
+
+  #[test]
+  fn bids_on_the_cards_dealt_before_the_bids() {
+    use Value::*;
+    let program = "bid 20 if total >= 10 and bank_open <= 6; bid 0 if deck_left < 10";
+    let mut strategy = ExprStrategy::parse("test", program).unwrap();
+    let player = Player::new(1);
+    let mut bid =
+      |cards: &[Value], bank| with_view(cards, Some(bank), |view| strategy.bid(&player, view));
+    assert_eq!(bid(&[Ten], Six), 20);
+    assert_eq!(bid(&[Ten], Ace), 1);
+    assert_eq!(bid(&[Two], Six), 1);
+  }
+
+  #[test]
+  fn errors_point_at_the_line_and_column() {
+    let error = |program: &str| ExprStrategy::parse("bad", program).err().unwrap();
+    assert_eq!(error("hit if tota < 15"), "1:8: Unknown value 'tota'");
+    assert_eq!(
+      error("hit if total"),
+      "1:8: Expected a condition, found a number"
+    );
+    assert_eq!(
+      error("hit if total < 15 or 3"),
+      "1:22: Expected a condition around 'or', found a number"
+    );
+    assert_eq!(
+      error("stand\ndraw if total < 15"),
+      "2:1: Expected a rule starting with hit, stand, double, split, surrender, buy or bid"
+    );
+    assert_eq!(error("hit if (total < 15"), "1:19: Expected ')'");
+    assert_eq!(
+      error("hit if money > 3"),
+      "1:8: 'money' is only known when bidding"
+    );
+    assert_eq!(error("hit if total ? 3"), "1:14: Unexpected '?'");
+    assert_eq!(
+      error("hit total"),
+      "1:5: Expected ';' or a new line after the rule"
+    );
+  }
+}
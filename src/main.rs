@@ -1,19 +1,83 @@
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
-use std::{collections::HashMap, fmt};
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::{cmp::Reverse, collections::HashMap, fmt};
 
-use console::style;
-use dialoguer::{Confirm, Select};
+mod bank_odds;
+mod bench;
+mod bot;
+mod casino;
+mod chart;
+mod client;
+mod compare;
+mod dutch;
+mod events;
+mod expr;
+mod pontoon;
+mod rules;
+mod scenario;
+mod server;
+mod solver;
+mod strategy;
+mod table;
+mod variant;
 
-struct PlayRecord {
-  open_card: Card,
-  closed_card: Card,
-  bought: bool,
-}
+use bot::Bot;
+use events::{Event, EventLog, Observer, Observers};
+use expr::ExprStrategy;
+use rules::{DeckComposition, Outcome, RedealTrigger, Rules, WorthScheme};
+use strategy::{
+  bank_strategy_by_name, play_strategy_by_name, BankStrategy, PlayStrategy, PlayerView, StandOn,
+};
+use table::DecisionTable;
+use variant::{open_bank_card, SeatHand};
 
 struct WinRecords {
-  win_records: Vec<PlayRecord>,
-  lose_records: Vec<PlayRecord>,
+  /// Hands won, pushed and lost per value of the open card, from Ace to Joker
+  wins_per_open_card: [u32; 14],
+  pushes_per_open_card: [u32; 14],
+  losses_per_open_card: [u32; 14],
+  /// Won and lost hands that drew more cards, per starting pair with the
+  /// highest value first
+  wins_with_bought: HashMap<(Value, Value), u32>,
+  losses_with_bought: HashMap<(Value, Value), u32>,
+  /// Number of hands and the players' net result per outcome
+  outcomes: HashMap<Outcome, (u32, i64)>,
+  /// Sum of all bids, to express the net results per unit bid
+  staked: u64,
+  /// Times each redeal was offered and taken
+  redeals: HashMap<RedealTrigger, (u32, u32)>,
+  redealt_hands: Tally,
+  declined_redeal_hands: Tally,
+  /// Players who took or declined a redeal this round
+  redealt: Vec<u32>,
+  declined_redeal: Vec<u32>,
+  /// Insurance bets against a bank natural
+  insurance: Tally,
+  /// Card worths of the game, to show the totals of recorded hands
+  worth: WorthScheme,
+}
+
+/// Hands, bids and net result of a group of hands.
+#[derive(Default)]
+struct Tally {
+  hands: u32,
+  staked: u64,
+  net: i64,
+}
+
+impl Tally {
+  fn add(&mut self, bid: u32, net: i64) {
+    self.hands += 1;
+    self.staked += bid as u64;
+    self.net += net;
+  }
+  /// Net result per unit bid
+  fn ev(&self) -> f64 {
+    self.net as f64 / self.staked as f64
+  }
 }
 fn percentage_bar(percentage: f64, max_percentage: f64, scale: u32) -> (f64, String) {
   let mut bar = String::new();
@@ -28,157 +92,189 @@ fn percentage_bar(percentage: f64, max_percentage: f64, scale: u32) -> (f64, Str
   (percentage, bar)
 }
 impl WinRecords {
-  fn new() -> WinRecords {
+  fn new(worth: WorthScheme) -> WinRecords {
     WinRecords {
-      win_records: Vec::new(),
-      lose_records: Vec::new(),
-    }
-  }
-  fn record_win(&mut self, hand: &Hand) {
-    let open_card = hand.cards[0].clone();
-    let closed_card = hand.cards[1].clone();
-    let bought = hand.cards.len() > 2;
-    let record = PlayRecord {
-      open_card,
-      closed_card,
-      bought,
-    };
-    self.win_records.push(record);
-  }
-  fn record_loss(&mut self, hand: &Hand) {
-    let open_card = hand.cards[0].clone();
-    let closed_card = hand.cards[1].clone();
-    let bought = hand.cards.len() > 2;
-    let record = PlayRecord {
-      open_card,
-      closed_card,
-      bought,
+      wins_per_open_card: [0; 14],
+      pushes_per_open_card: [0; 14],
+      losses_per_open_card: [0; 14],
+      wins_with_bought: HashMap::new(),
+      losses_with_bought: HashMap::new(),
+      outcomes: HashMap::new(),
+      staked: 0,
+      redeals: HashMap::new(),
+      redealt_hands: Tally::default(),
+      insurance: Tally::default(),
+      declined_redeal_hands: Tally::default(),
+      redealt: Vec::new(),
+      declined_redeal: Vec::new(),
+      worth,
+    }
+  }
+  fn record(
+    &mut self,
+    player: u32,
+    (open, closed): (Value, Value),
+    cards: usize,
+    outcome: Outcome,
+    bid: u32,
+    net: i64,
+  ) {
+    let pair = match open.to_u32() >= closed.to_u32() {
+      true => (open, closed),
+      false => (closed, open),
     };
-    self.lose_records.push(record);
+    let bought = cards > 2;
+    let index = open.to_u32() as usize - 1;
+    if outcome.is_win() {
+      self.wins_per_open_card[index] += 1;
+      if bought {
+        *self.wins_with_bought.entry(pair).or_insert(0) += 1;
+      }
+    } else if outcome == Outcome::Push {
+      self.pushes_per_open_card[index] += 1;
+    } else {
+      self.losses_per_open_card[index] += 1;
+      if bought {
+        *self.losses_with_bought.entry(pair).or_insert(0) += 1;
+      }
+    }
+    let (count, total) = self.outcomes.entry(outcome).or_insert((0, 0));
+    *count += 1;
+    *total += net;
+    self.staked += bid as u64;
+    if self.redealt.contains(&player) {
+      self.redealt_hands.add(bid, net);
+    }
+    if self.declined_redeal.contains(&player) {
+      self.declined_redeal_hands.add(bid, net);
+    }
   }
-  fn display_stats(&self) {
-    // Wins per open cards
-    let mut wins_per_open_card = HashMap::<Value, u32>::new();
-    let mut losses_per_open_card = HashMap::<Value, u32>::new();
-
-    for record in &self.win_records {
-      let value = record.open_card.value;
-      let count = wins_per_open_card.entry(value).or_insert(0);
-      *count += 1;
-    }
-
-    for record in &self.lose_records {
-      let value = record.open_card.value;
-      let count = losses_per_open_card.entry(value).or_insert(0);
-      *count += 1;
-    }
-    // Sort the hasmaps by value
-    let mut wins_per_open_card: Vec<_> = wins_per_open_card.into_iter().collect();
-    wins_per_open_card.sort_by(|a, b| b.1.cmp(&a.1));
-    let mut losses_per_open_card: Vec<_> = losses_per_open_card.into_iter().collect();
-    losses_per_open_card.sort_by(|a, b| b.1.cmp(&a.1));
-
-    println!("Wins per open card:");
-    let total_wins = self.win_records.len() as f64;
-    for (value, count) in &wins_per_open_card {
-      let (percentage, bar) = percentage_bar((*count as f64 / total_wins) * 100.0, 100.0, 200);
-      println!(" {:>2}: {:>9} ({:>5.2}%) {}", value, count, percentage, bar);
-    }
-
-    println!("Losses per open card:");
-    let total_losses = self.lose_records.len() as f64;
-    for (value, count) in &losses_per_open_card {
-      let (percentage, bar) = percentage_bar((*count as f64 / total_losses) * 100.0, 100.0, 200);
-      println!(
-        "  {:>2}: {:>9} ({:>5.2}%) {}",
-        value, count, percentage, bar
-      );
+  fn record_redeal(&mut self, player: u32, trigger: RedealTrigger, taken: bool) {
+    let (offered, taken_count) = self.redeals.entry(trigger).or_insert((0, 0));
+    *offered += 1;
+    if taken {
+      *taken_count += 1;
+      self.redealt.push(player);
+    } else {
+      self.declined_redeal.push(player);
     }
-
-    // Best open closed combination with bought
-    let wins_with_bought = self
-      .win_records
-      .iter()
-      .filter(|record| record.bought)
-      .collect::<Vec<_>>();
-    // Sort so biggest value card is open and smallest is closed
-    let mut wins_with_bought: Vec<_> = wins_with_bought
+  }
+  /// Number of hands played
+  fn hands(&self) -> u32 {
+    self.outcomes.values().map(|(count, _)| count).sum()
+  }
+  /// Net result of the players over all hands
+  fn net(&self) -> i64 {
+    self.outcomes.values().map(|(_, net)| net).sum()
+  }
+  fn display_redeals(&self) {
+    let mut redeals: Vec<_> = self
+      .redeals
       .iter()
-      .map(|record| {
-        let mut cards = vec![record.open_card.clone(), record.closed_card.clone()];
-        cards.sort_by(|a, b| b.value.to_u32().cmp(&a.value.to_u32()));
-        (cards[0].value, cards[1].value)
-      })
+      .map(|(trigger, counts)| (trigger.to_string(), counts))
       .collect();
-    wins_with_bought.sort_by(|a, b| a.0.to_u32().cmp(&b.0.to_u32()));
-    let mut wins_with_bought =
-      wins_with_bought
-        .into_iter()
-        .fold(HashMap::<(Value, Value), u32>::new(), |mut acc, value| {
-          let count = acc.entry(value).or_insert(0);
-          *count += 1;
-          acc
-        });
-    let mut wins_with_bought: Vec<_> = wins_with_bought.into_iter().collect();
-    wins_with_bought.sort_by(|a, b| b.1.cmp(&a.1));
-    wins_with_bought.truncate(40);
-    println!("Best open closed combination with bought:");
-    for ((open, closed), count) in &wins_with_bought {
-      let mut hand = Hand::new();
-      let mut open_card = Card::new(Suit::Hearts, *open);
-      open_card.open();
-      let mut closed_card = Card::new(Suit::Hearts, *closed);
-      closed_card.open();
-      hand.add_card(open_card);
-      hand.add_card(closed_card);
-      let (percentage, bar) = percentage_bar((*count as f64 / total_wins) * 100.0, 100.0, 1000);
-
+    redeals.sort();
+    println!("Redeals:");
+    if redeals.is_empty() {
+      println!("  No redeals");
+      return;
+    }
+    let hands = self.hands() as f64;
+    for (trigger, (offered, taken)) in redeals {
       println!(
-        " {:<20} {:<6} ({:>5.4}%) {}",
-        format!("{}", hand),
+        "  {:<10} offered {:>9} ({:>5.2}%), taken {:>9}",
+        format!("{}:", trigger),
+        offered,
+        (*offered as f64 / hands) * 100.0,
+        taken
+      );
+    }
+    let overall = self.net() as f64 / self.staked as f64;
+    println!("  EV of all hands:        {:>+8.4}", overall);
+    if self.redealt_hands.hands > 0 {
+      println!(
+        "  EV after a redeal:      {:>+8.4} ({} hands)",
+        self.redealt_hands.ev(),
+        self.redealt_hands.hands
+      );
+    }
+    if self.declined_redeal_hands.hands > 0 {
+      println!(
+        "  EV of declined redeals: {:>+8.4} ({} hands)",
+        self.declined_redeal_hands.ev(),
+        self.declined_redeal_hands.hands
+      );
+    }
+  }
+  fn display_outcomes(&self) {
+    let hands = self.hands() as f64;
+    let staked = self.staked as f64;
+    println!("Outcomes per hand played (EV per 1$ bid):");
+    for outcome in Outcome::ALL {
+      let (count, net) = self.outcomes.get(&outcome).copied().unwrap_or((0, 0));
+      let (percentage, bar) = percentage_bar((count as f64 / hands) * 100.0, 100.0, 200);
+      println!(
+        "  {:<13} {:>9} ({:>5.2}%) EV {:>+8.4} {}",
+        format!("{}:", outcome),
         count,
         percentage,
+        net as f64 / staked,
         bar
       );
     }
-
-    // Worst open closed combination with bought
-    let losses_with_bought = self
-      .lose_records
-      .iter()
-      .filter(|record| record.bought)
-      .collect::<Vec<_>>();
-    // Sort so biggest value card is open and smallest is closed
-    let mut losses_with_bought: Vec<_> = losses_with_bought
+    let net = self.net();
+    println!(
+      "  {:<13} {:>9}          EV {:>+8.4}",
+      "total:",
+      hands,
+      net as f64 / staked
+    );
+    if self.insurance.hands > 0 {
+      println!(
+        "  {:<13} {:>9} ({:>5.2}%) EV {:>+8.4}",
+        "insurance:",
+        self.insurance.hands,
+        (self.insurance.hands as f64 / hands) * 100.0,
+        self.insurance.ev()
+      );
+    }
+  }
+  fn display_per_open_card(title: &str, counts: &[u32; 14]) {
+    let total = counts.iter().sum::<u32>() as f64;
+    let mut counts: Vec<_> = counts
       .iter()
-      .map(|record| {
-        let mut cards = vec![record.open_card.clone(), record.closed_card.clone()];
-        cards.sort_by(|a, b| b.value.to_u32().cmp(&a.value.to_u32()));
-        (cards[0].value, cards[1].value)
-      })
+      .enumerate()
+      .filter(|(_, count)| **count > 0)
+      .map(|(index, count)| (Value::from_u32(index as u32 + 1), *count))
       .collect();
-    losses_with_bought.sort_by(|a, b| a.0.to_u32().cmp(&b.0.to_u32()));
-    let mut losses_with_bought = losses_with_bought.into_iter().fold(
-      HashMap::<(Value, Value), u32>::new(),
-      |mut acc, value| {
-        let count = acc.entry(value).or_insert(0);
-        *count += 1;
-        acc
-      },
-    );
-    let mut losses_with_bought: Vec<_> = losses_with_bought.into_iter().collect();
-    losses_with_bought.sort_by(|a, b| b.1.cmp(&a.1));
-    losses_with_bought.truncate(40);
-    println!("Worst open closed combination with bought:");
-    for ((open, closed), count) in &losses_with_bought {
+    counts.sort_by_key(|a| Reverse(a.1));
+    println!("{title}");
+    for (value, count) in &counts {
+      let (percentage, bar) = percentage_bar((*count as f64 / total) * 100.0, 100.0, 200);
+      println!(
+        "  {:>2}: {:>9} ({:>5.2}%) {}",
+        value, count, percentage, bar
+      );
+    }
+  }
+  fn display_combinations(
+    &self,
+    title: &str,
+    combinations: &HashMap<(Value, Value), u32>,
+    total: u32,
+  ) {
+    let mut combinations: Vec<_> = combinations.iter().collect();
+    combinations.sort_by_key(|a| Reverse(*a.1));
+    combinations.truncate(40);
+    println!("{title}");
+    for ((open, closed), count) in combinations {
       let mut hand = Hand::new();
-      let mut open_card = Card::new(Suit::Hearts, *open);
-      open_card.open();
-      let mut closed_card = Card::new(Suit::Hearts, *closed);
-      closed_card.open();
-      hand.add_card(open_card);
-      hand.add_card(closed_card);
-      let (percentage, bar) = percentage_bar((*count as f64 / total_losses) * 100.0, 100.0, 1000);
+      for value in [open, closed] {
+        let mut card = Card::new(Suit::Hearts, *value);
+        card.open();
+        hand.add_card(card, &self.worth);
+      }
+      let (percentage, bar) = percentage_bar((*count as f64 / total as f64) * 100.0, 100.0, 1000);
 
       println!(
         " {:<20} {:<6} ({:>5.4}%) {}",
@@ -189,362 +285,663 @@ impl WinRecords {
       );
     }
   }
+  fn display_stats(&self) {
+    WinRecords::display_per_open_card("Wins per open card:", &self.wins_per_open_card);
+    WinRecords::display_per_open_card("Losses per open card:", &self.losses_per_open_card);
+    if self.pushes_per_open_card.iter().any(|count| *count > 0) {
+      WinRecords::display_per_open_card("Pushes per open card:", &self.pushes_per_open_card);
+    }
+
+    let total_wins = self.wins_per_open_card.iter().sum();
+    self.display_combinations(
+      "Best open closed combination with bought:",
+      &self.wins_with_bought,
+      total_wins,
+    );
+    let total_losses = self.losses_per_open_card.iter().sum();
+    self.display_combinations(
+      "Worst open closed combination with bought:",
+      &self.losses_with_bought,
+      total_losses,
+    );
+
+    self.display_outcomes();
+    self.display_redeals();
+  }
+}
+
+impl Observer for WinRecords {
+  fn observe(&mut self, event: &Event) {
+    match *event {
+      Event::RoundStarted { .. } => {
+        self.redealt.clear();
+        self.declined_redeal.clear();
+      }
+      Event::Redeal {
+        player,
+        trigger,
+        taken,
+      } => self.record_redeal(player, trigger, taken),
+      Event::Insurance { stake, net, .. } => self.insurance.add(stake, net),
+      Event::Payout {
+        player,
+        start,
+        cards,
+        outcome,
+        bid,
+        net,
+        ..
+      } => self.record(player, start, cards, outcome, bid, net),
+      _ => {}
+    }
+  }
+}
+
+#[derive(Default)]
+struct BankRecords {
+  /// Number of rounds each bank survived before going broke
+  tenures: Vec<u32>,
+}
+
+impl Observer for BankRecords {
+  fn observe(&mut self, event: &Event) {
+    if let Event::BankBroke { rounds } = *event {
+      self.tenures.push(rounds);
+    }
+  }
+}
+
+impl BankRecords {
+  /// Shows how long the banks lasted against `strategy_mix`, the players of
+  /// the game.
+  fn display_stats(&self, strategy_mix: &str) {
+    println!("Bank survival per strategy mix:");
+    if self.tenures.is_empty() {
+      println!("  No bank went broke");
+    } else {
+      let mut tenures = self.tenures.clone();
+      tenures.sort_unstable();
+      let average = tenures.iter().map(|t| *t as f64).sum::<f64>() / tenures.len() as f64;
+      println!("  {strategy_mix}:");
+      println!("    Banks broke:    {:>9}", tenures.len());
+      println!("    Average tenure: {:>9.1} rounds", average);
+      println!("    Shortest:       {:>9} rounds", tenures[0]);
+      println!(
+        "    Median:         {:>9} rounds",
+        tenures[tenures.len() / 2]
+      );
+      println!(
+        "    Longest:        {:>9} rounds",
+        tenures[tenures.len() - 1]
+      );
+    }
+  }
+}
+
+/// Seats taken when no `--player` is given
+const DEFAULT_SEATS: [&str; 4] = ["deck-aware", "fixed-6", "fixed-6", "fixed-6"];
+
+/// The value after every `flag` in the arguments, empty if `flag` is last.
+fn flag_values<'a>(args: &'a [String], flag: &str) -> Vec<&'a str> {
+  args
+    .iter()
+    .enumerate()
+    .filter(|(_, arg)| *arg == flag)
+    .map(|(index, _)| args.get(index + 1).map(String::as_str).unwrap_or(""))
+    .collect()
+}
+
+fn exit_with(error: &str) -> ! {
+  eprintln!("{error}");
+  std::process::exit(1);
+}
+
+/// The rules set with `--rule key=value`, on top of the Dutch defaults.
+fn parse_rules(args: &[String]) -> Rules {
+  let mut rules = Rules::default();
+  let mut rule_args = flag_values(args, "--rule");
+  // The variant resets the other rules, so it goes first
+  rule_args.sort_by_key(|rule| !rule.starts_with("variant="));
+  for rule in rule_args {
+    let result = match rule.split_once('=') {
+      Some((key, value)) => rules.set(key, value),
+      None => Err(format!("Expected a rule like key=value, got '{rule}'")),
+    };
+    if let Err(error) = result {
+      exit_with(&error);
+    }
+  }
+  rules
+}
+
+/// The bank strategy picked with `--bank`, standing on 17 by default.
+fn parse_bank_strategy(args: &[String]) -> Box<dyn BankStrategy> {
+  match flag_values(args, "--bank").first() {
+    Some(name) => bank_strategy_by_name(name).unwrap_or_else(|| {
+      exit_with(&format!(
+        "Unknown bank strategy '{name}'\nExpected stand-on-<n>, beat-majority or max-expected-profit"
+      ))
+    }),
+    None => Box::new(StandOn::new(17)),
+  }
+}
+
+/// A strategy by name, `table:<file>` for a decision table, `expr:<rules>`
+/// for rules in the expression language or `bot:<command>` for a program.
+fn parse_play_strategy(name: &str) -> Box<dyn PlayStrategy> {
+  if let Some(command) = name.strip_prefix("bot:") {
+    return Box::new(
+      Bot::spawn(name, command, bot::TIMEOUT).unwrap_or_else(|error| exit_with(&error)),
+    );
+  }
+  if let Some(path) = name.strip_prefix("table:") {
+    return Box::new(DecisionTable::load(path).unwrap_or_else(|error| exit_with(&error)));
+  }
+  if let Some(program) = name.strip_prefix("expr:") {
+    let strategy = ExprStrategy::parse(name, program);
+    return Box::new(strategy.unwrap_or_else(|error| exit_with(&format!("In {name}:\n{error}"))));
+  }
+  play_strategy_by_name(name).unwrap_or_else(|| {
+    exit_with(&format!(
+      "Unknown player strategy '{name}'\nExpected deck-aware, fixed-<n>, basic, optimal, composition, table:<file>, expr:<rules> or bot:<command>"
+    ))
+  })
+}
+
+/// Exits if `name` is not a strategy `parse_play_strategy` can make, without
+/// starting the program of a bot.
+fn check_play_strategy(name: &str) {
+  match name.strip_prefix("bot:") {
+    Some(command) if command.trim().is_empty() => {
+      exit_with(&format!("No command to run in '{name}'"))
+    }
+    Some(_) => {}
+    None => {
+      parse_play_strategy(name);
+    }
+  }
+}
+
+/// Card values separated by commas, like `10,6` or `A,K`.
+fn parse_values(list: &str) -> Result<Vec<Value>, String> {
+  list
+    .split(',')
+    .filter(|value| !value.is_empty())
+    .map(str::parse)
+    .collect()
 }
 
 fn main() {
+  let args: Vec<String> = std::env::args().collect();
+  match args.get(1).map(String::as_str) {
+    Some("bench") => return bench::run(),
+    Some("scenario") => return scenario::run(&args),
+    Some("bank-odds") => return bank_odds::run(&args),
+    Some("solve") => return solver::run(&args),
+    Some("compare") => return compare::run(&args),
+    Some("chart") => return chart::run(&args),
+    Some("server") => return server::run(&args),
+    Some("client") => return client::run(&args),
+    _ => {}
+  }
   println!("Hello, world!");
+  let rules = parse_rules(&args);
+  let bank_strategy = parse_bank_strategy(&args);
+  let mut seats = flag_values(&args, "--player");
+  if seats.is_empty() {
+    seats = DEFAULT_SEATS.to_vec();
+  }
+  rules
+    .check_deck(seats.len())
+    .unwrap_or_else(|error| exit_with(&error));
+  let stack = flag_values(&args, "--stack")
+    .first()
+    .map(|list| parse_values(list).unwrap_or_else(|error| exit_with(&error)));
+
   let bank = Player::new(0);
   println!("Bank: {bank}");
-  let player1 = Player::new(1);
-  println!("{player1}");
-  let player2 = Player::new(2);
-  println!("{player2}");
-  let player3 = Player::new(3);
-  let player4 = Player::new(4);
-
-  let mut game = Game::new(bank);
-  game.add_player(player1);
-  game.add_player(player2);
-  game.add_player(player3);
-  game.add_player(player4);
-  println!("");
+
+  println!("{rules}");
+  let mut game = Game::new(bank, bank_strategy, rules, StdRng::from_entropy());
+  for (index, name) in seats.iter().enumerate() {
+    game.add_player(Player::new(index as u32 + 1), parse_play_strategy(name));
+  }
+  if let Some(stack) = stack {
+    game.stack(stack).unwrap_or_else(|error| exit_with(&error));
+  }
+  if let Some(rounds) = flag_values(&args, "--log").first() {
+    let rounds = rounds
+      .parse()
+      .unwrap_or_else(|_| exit_with(&format!("Invalid number of rounds to log '{rounds}'")));
+    game.add_observer(Box::new(EventLog { rounds }));
+  }
+  println!();
   println!("GAME STATE: \n{game}");
-  let mut games_won_1 = 0;
-  let mut games_won_2 = 0;
-  let mut games_won_bank = 0;
+  // Rounds each player ended up, even or down
+  let mut results = vec![(0u32, 0u32, 0u32); seats.len()];
   let rounds = 10_000_000;
   let bar = indicatif::ProgressBar::new(rounds);
-  for round in 0..rounds {
+  for _ in 0..rounds {
     bar.inc(1);
-    // let should_continue = Confirm::new()
-    //   .with_prompt("New round?")
-    //   .default(true)
-    //   .interact()
-    //   .unwrap();
-    // if !should_continue {
-    //   break;
-    // }
-    game.start_round();
-    // println!("");
-    // println!("GAME STATE: \n{game}");
-    let players = game.players.clone();
-    for player in players {
-      if player.bid == 0 {
-        continue;
-      }
-      game.play_player(&player);
-      // println!("\n");
-    }
-    game.play_bank();
-    // println!("GAME STATE: \n{game}");
+    game.play_round().unwrap_or_else(|error| exit_with(&error));
 
-    if game.players.iter().find(|p| p.id == 1).unwrap().money > 100 {
-      games_won_1 += 1;
-    } else {
-      games_won_bank += 1;
-    }
-    if game.players.iter().find(|p| p.id == 2).unwrap().money > 100 {
-      games_won_2 += 1;
-    } else {
-      games_won_bank += 1;
+    for player in &game.players {
+      let (won, pushed, lost) = &mut results[player.id as usize - 1];
+      if player.money > 100 {
+        *won += 1;
+      } else if player.money == 100 {
+        *pushed += 1;
+      } else {
+        *lost += 1;
+      }
     }
     game.clean_up();
   }
   bar.finish();
   println!("\n\n");
+  for (index, (won, pushed, lost)) in results.iter().enumerate() {
+    let percentage = |count: &u32| (*count as f64 / rounds as f64) * 100.0;
+    println!(
+      "Player {} ({}): won {:>9} ({:.2}%), pushed {:>9} ({:.2}%), lost {:>9} ({:.2}%)",
+      index + 1,
+      seats[index],
+      won,
+      percentage(won),
+      pushed,
+      percentage(pushed),
+      lost,
+      percentage(lost)
+    );
+  }
+
+  game.records.borrow().display_stats();
+  game
+    .bank_records
+    .borrow()
+    .display_stats(&game.strategy_mix());
   println!(
-    "Games won by player 1: {games_won_1:>6} ({percentage:.2}%)",
-    games_won_1 = games_won_1,
-    percentage = (games_won_1 as f64 / rounds as f64) * 100.0
-  );
-  println!(
-    "Games won by player 2: {games_won_2:>6} ({percentage:.2}%)",
-    games_won_2 = games_won_2,
-    percentage = (games_won_2 as f64 / rounds as f64) * 100.0
-  );
-  println!(
-    "Games won by bank:     {games_won_bank:>6} ({percentage:.2}%)",
-    games_won_bank = games_won_bank,
-    percentage = (games_won_bank as f64 / (rounds as f64 * 2 as f64)) * 100.0
+    "Current bank still standing after {} rounds",
+    game.bank_rounds_played
   );
-
-  game.records.display_stats();
 }
 
 struct Game {
   deck: Deck,
   bank: Player,
+  bank_strategy: Box<dyn BankStrategy>,
+  /// Money the bank starts each tenure with
+  bank_bankroll: u32,
   players: Vec<Player>,
+  strategies: HashMap<u32, Box<dyn PlayStrategy>>,
+  rules: Rules,
   bank_rounds_played: u32,
-  records: WinRecords,
+  /// Statistics of every hand and bank, gathered from the game's events
+  records: Rc<RefCell<WinRecords>>,
+  bank_records: Rc<RefCell<BankRecords>>,
+  /// The hands of every seat in the current round, kept between rounds so
+  /// that they don't have to be allocated again
+  seats: Vec<Vec<SeatHand>>,
+  /// Decides the seating order each round
+  rng: StdRng,
+  /// Values dealt first every round
+  stacked: Vec<Value>,
+  /// Values taken out of the deck every round
+  removed: Vec<Value>,
+  /// Rounds played over the whole game
+  rounds: u32,
+  observers: Observers,
 }
 
 struct Deck {
   cards: Vec<Card>,
+  /// Cards taken out of play during the round, shuffled back in when the
+  /// deck runs out
+  discards: Vec<Card>,
+  /// Every card of the deck, to start each round from without allocating
+  full: Vec<Card>,
+  /// Cards dealt face up since the deck was last reset
+  open: Vec<Card>,
+  worth: WorthScheme,
+  /// Sum of the lowest worths of the cards left
+  worth_sum: u32,
+  rng: StdRng,
 }
 
 impl Deck {
-  fn new() -> Deck {
+  fn new(composition: &DeckComposition, worth: &WorthScheme, rng: StdRng) -> Deck {
     let mut deck = Vec::new();
-    for suit in vec![Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs] {
-      for value in vec![
-        Value::Ace,
-        Value::Two,
-        Value::Three,
-        Value::Four,
-        Value::Five,
-        Value::Six,
-        Value::Seven,
-        Value::Eight,
-        Value::Nine,
-        Value::Ten,
-        Value::Jack,
-        Value::Queen,
-        Value::King,
-      ] {
-        deck.push(Card::new(suit, value));
+    for _ in 0..composition.decks {
+      for suit in [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs] {
+        for value in Value::ALL {
+          for _ in 0..composition.copies(value) {
+            deck.push(Card::new(suit, value));
+          }
+        }
+      }
+      for joker in 0..composition.jokers {
+        let suit = match joker % 2 {
+          0 => Suit::Spades,
+          _ => Suit::Hearts,
+        };
+        deck.push(Card::new(suit, Value::Joker));
       }
     }
-    Deck { cards: deck }
+    let mut deck = Deck {
+      cards: deck.clone(),
+      discards: Vec::with_capacity(deck.len()),
+      open: Vec::with_capacity(deck.len()),
+      full: deck,
+      worth: worth.clone(),
+      worth_sum: 0,
+      rng,
+    };
+    deck.count_worth();
+    deck
   }
 
   fn shuffle(&mut self) {
-    let mut rng = thread_rng();
-    self.cards.shuffle(&mut rng);
+    self.cards.shuffle(&mut self.rng);
   }
 
-  fn draw(&mut self) -> Card {
-    self.cards.pop().expect("No more cards in deck")
+  /// Puts every card back and shuffles.
+  fn reset(&mut self) {
+    self.cards.clear();
+    self.cards.extend_from_slice(&self.full);
+    self.discards.clear();
+    self.open.clear();
+    self.count_worth();
+    self.shuffle();
   }
 
-  fn expected_value(&self) -> f64 {
-    self
-      .cards
-      .iter()
-      .fold(0.0, |acc, card| acc + card.worth() as f64)
-      / self.cards.len() as f64
+  fn count_worth(&mut self) {
+    self.worth_sum = self.cards.iter().map(|card| self.lowest_worth(*card)).sum();
   }
-}
 
-impl Game {
-  fn new(bank: Player) -> Game {
-    let mut deck = Deck::new();
-    deck.shuffle();
-    Game {
-      deck: deck,
-      bank: bank,
-      players: Vec::new(),
-      bank_rounds_played: 0,
-      records: WinRecords::new(),
-    }
+  fn lowest_worth(&self, card: Card) -> u32 {
+    self.worth.lowest(card.value())
   }
-  fn add_player(&mut self, player: Player) {
-    self.players.push(player);
-  }
-  fn start_round(&mut self) {
-    // Shuffle players
-    let mut rng = thread_rng();
-    self.players.shuffle(&mut rng);
 
-    // Deal open card
-    for player in &mut self.players {
-      let mut card = self.deck.draw();
-      card.open();
-      player.hand.add_card(card);
+  /// Draws the top card, shuffling the discards back in when the deck runs
+  /// out. Fails when every card is in a hand.
+  fn draw(&mut self) -> Result<Card, String> {
+    if self.cards.is_empty() {
+      self.cards.append(&mut self.discards);
+      self.count_worth();
+      self.shuffle();
     }
-    let mut card = self.deck.draw();
-    card.open();
-    self.bank.hand.add_card(card);
+    let card = self.cards.pop().ok_or("No more cards in deck")?;
+    self.worth_sum -= self.lowest_worth(card);
+    Ok(card)
+  }
 
-    // Let players place their bid
-    for player in &mut self.players {
-      let placed_bid = generate_bid(player);
-      player.bid = placed_bid;
-      player.money -= placed_bid;
-      self.bank.money -= placed_bid;
+  /// Draws a card into a hand, visible to whoever `visibility` says, and
+  /// returns it.
+  fn deal(&mut self, hand: &mut Hand, visibility: Visibility) -> Result<Card, String> {
+    let mut card = self.draw()?;
+    card.visibility = visibility;
+    if visibility == Visibility::All {
+      self.open.push(card);
     }
+    hand.add_card(card, &self.worth);
+    Ok(card)
+  }
 
-    // Deal closed cards
-    for player in &mut self.players {
-      let mut card = self.deck.draw();
-      card.open_for_owner();
-      player.hand.add_card(card);
-
-      loop {
-        if player.hand.cards.iter().any(|c| c.value == Value::Seven)
-          && player.hand.cards.iter().any(|c| c.value == Value::Eight)
-        {
-          player.hand.cards.clear();
-          let mut card1 = self.deck.draw();
-          card1.open();
-          player.hand.add_card(card1);
-
-          let mut card2 = self.deck.draw();
-          card2.open_for_owner();
-          player.hand.add_card(card2);
-        } else {
-          break;
+  /// Moves cards of `values` to the top of the deck, to be drawn in that
+  /// order. The rest of the deck stays shuffled below them.
+  fn stack(&mut self, values: &[Value]) -> Result<(), String> {
+    let mut stacked = Vec::with_capacity(values.len());
+    for value in values {
+      match self.take(*value) {
+        Some(card) => stacked.push(card),
+        None => {
+          self.cards.extend(stacked);
+          return Err(format!(
+            "Not enough {} in the deck to stack",
+            value.to_string().trim()
+          ));
         }
       }
     }
+    self.cards.extend(stacked.into_iter().rev());
+    Ok(())
+  }
 
-    let card = self.deck.draw();
-    self.bank.hand.add_card(card);
+  /// Takes a card of each of `values` out of the deck until it is reset.
+  fn remove(&mut self, values: &[Value]) -> Result<(), String> {
+    for value in values {
+      let card = self.take(*value).ok_or(format!(
+        "Not enough {} in the deck to remove",
+        value.to_string().trim()
+      ))?;
+      self.worth_sum -= self.lowest_worth(card);
+    }
+    Ok(())
   }
-  fn play_player(&mut self, player: &Player) {
-    let player = &mut self
-      .players
-      .iter_mut()
-      .find(|p| p.id == player.id)
-      .expect(format!("Player with id {} does not exist", player.id).as_str());
-
-    if player.bid == 0 {
-      panic!("Player {} has no bid, but is still being played", player.id);
-    }
-
-    // println!("Playing player {}", player.id);
-    loop {
-      let possible_values = player.hand.possible_values();
-      let greatest_value = player.hand.greatest_value();
-      let smallest_value = player.hand.smallest_value();
-
-      // println!("  Possible values: {values:?}", values = possible_values);
-      if smallest_value > 21 {
-        // println!("  Bust!");
-        self.bank.money += player.bid * 2;
-        player.bid = 0;
-        self.records.record_loss(&player.hand);
-        break;
-      } else if player.hand.possible_values().contains(&21) {
-        // println!("  Blackjack!");
-        player.money += player.bid * 2;
-        player.bid = 0;
-        self.records.record_win(&player.hand);
-        break;
-      }
 
-      // println!("  Greatest value: {value}", value = greatest_value);
-      match player.id {
-        1 => {
-          let deck = &self.deck;
-          let expected_value = deck.expected_value();
-          if greatest_value as f64 + expected_value > 21.0 {
-            // println!("  Staying A");
-            break;
-          } else {
-            let mut card = self.deck.draw();
-            card.open();
-            // println!("  Drawing a card: {card}", card = card);
-            player.hand.add_card(card);
-          }
-        }
-        _ => {
-          let expected_value = 6.0;
-          if smallest_value as f64 + expected_value > 21.0 {
-            // println!("  Staying B");
-            break;
-          } else {
-            let mut card = self.deck.draw();
-            card.open();
-            // println!("  Drawing a card: {card}", card = card);
-            player.hand.add_card(card);
-          }
-        }
-        // 3 => {
-        //   if greatest_value >= 17 {
-        //     // println!("  Staying");
-        //     break;
-        //   } else {
-        //     let mut card = self.deck.draw();
-        //     card.open();
-        //     // println!("  Drawing a card: {card}", card = card);
-        //     player.hand.add_card(card);
-        //   }
-        // }
-        // _ => panic!("Player id {} does not exist", player.id),
-      }
-      if player.hand.cards.len() > 6 {
-        // println!("  player won by cards");
-        player.money += player.bid * 2;
-        self.records.record_win(&player.hand);
-        player.bid = 0;
-        break;
-      }
+  /// Takes a random card of `value` out of the deck. The other cards keep
+  /// their order, so a shuffled deck stays shuffled without shuffling again.
+  fn take(&mut self, value: Value) -> Option<Card> {
+    let copies = self
+      .cards
+      .iter()
+      .filter(|card| card.value() == value)
+      .count();
+    if copies == 0 {
+      return None;
     }
+    let copy = self.rng.gen_range(0..copies);
+    let (index, _) = self
+      .cards
+      .iter()
+      .enumerate()
+      .filter(|(_, card)| card.value() == value)
+      .nth(copy)?;
+    Some(self.cards.remove(index))
   }
-  fn play_bank(&mut self) {
-    self.bank_rounds_played += 1;
-    // println!("Playing bank");
-    self.bank.hand.cards.iter_mut().for_each(|f| f.open());
-    loop {
-      let bank = &mut self.bank;
-
-      // println!(
-      //   "  Possible values: {values:?}",
-      //   values = bank.hand.possible_values()
-      // );
-      if bank.hand.smallest_value() > 21 {
-        // println!(
-        //   "  Bank died after {rounds} rounds",
-        //   rounds = self.bank_rounds_played
-        // );
-        self.bank_rounds_played = 0;
-        for player in &mut self.players {
-          if player.bid == 0 {
-            continue;
-          }
-          player.money += player.bid * 2;
-          self.records.record_win(&player.hand);
-          player.bid = 0;
-        }
-        break;
-      }
 
-      let greatest_value = bank.hand.greatest_value();
-      // println!("  Greatest value: {value}", value = greatest_value);
-      if greatest_value >= 17 {
-        // println!("  Staying");
-        for player in &mut self.players {
-          if player.bid == 0 {
-            continue;
-          }
-          let player_value = player.hand.greatest_value();
-          if player_value > greatest_value {
-            // println!("  Player {} wins against the bank!", player.id);
-            player.money += player.bid * 2;
-            self.records.record_win(&player.hand);
-            player.bid = 0;
-          } else {
-            // println!("  Player {} loses against the bank!", player.id);
-            self.bank.money += player.bid * 2;
-            self.records.record_loss(&player.hand);
-            player.bid = 0;
-          }
-        }
-        break;
-      } else {
-        // println!("  Drawing");
-        let mut card = self.deck.draw();
+  fn discard(&mut self, hand: &mut Hand) {
+    self.discards.extend_from_slice(hand.cards());
+    hand.clear();
+  }
+
+  /// Number of cards left per value, ordered from Ace to Joker, each with an
+  /// open card of that value to stand in for them
+  fn value_counts(&self) -> Vec<(Card, u32)> {
+    self
+      .counts()
+      .iter()
+      .enumerate()
+      .filter(|(_, count)| **count > 0)
+      .map(|(index, count)| {
+        let mut card = Card::new(Suit::Spades, Value::from_u32(index as u32 + 1));
         card.open();
-        bank.hand.add_card(card);
+        (card, *count)
+      })
+      .collect()
+  }
+
+  /// Number of cards left per value, from Ace to Joker
+  fn counts(&self) -> [u32; 14] {
+    let mut counts = [0; 14];
+    for card in &self.cards {
+      counts[card.value().to_u32() as usize - 1] += 1;
+    }
+    counts
+  }
+
+  /// Number of cards per value the owner of `hand` has not seen this round,
+  /// from Ace to Joker: the full deck without the open cards and their own.
+  fn unseen(&self, hand: &Hand) -> [u32; 14] {
+    let mut counts = [0u32; 14];
+    for card in &self.full {
+      counts[card.value().to_u32() as usize - 1] += 1;
+    }
+    let own = hand
+      .cards()
+      .iter()
+      .filter(|card| card.visibility != Visibility::All);
+    // A deck that ran out mid-round deals seen cards again
+    for card in self.open.iter().chain(own) {
+      let count = &mut counts[card.value().to_u32() as usize - 1];
+      *count = count.saturating_sub(1);
+    }
+    counts
+  }
+
+  fn expected_value(&self) -> f64 {
+    self.worth_sum as f64 / self.cards.len() as f64
+  }
+}
+
+impl Game {
+  /// A game without players. Every shuffle and the seating come from `rng`,
+  /// so a seeded one plays the same rounds every time.
+  fn new(
+    bank: Player,
+    bank_strategy: Box<dyn BankStrategy>,
+    rules: Rules,
+    mut rng: StdRng,
+  ) -> Game {
+    let deck_rng = StdRng::from_rng(&mut rng).expect("Could not seed the deck");
+    let mut deck = Deck::new(&rules.deck, &rules.worth, deck_rng);
+    deck.shuffle();
+    let records = Rc::new(RefCell::new(WinRecords::new(rules.worth.clone())));
+    let bank_records = Rc::new(RefCell::new(BankRecords::default()));
+    let mut observers = Observers::default();
+    observers.add(Box::new(records.clone()));
+    observers.add(Box::new(bank_records.clone()));
+    Game {
+      deck,
+      bank_bankroll: bank.money,
+      bank,
+      bank_strategy,
+      players: Vec::new(),
+      strategies: HashMap::new(),
+      rules,
+      bank_rounds_played: 0,
+      records,
+      bank_records,
+      seats: Vec::new(),
+      rng,
+      stacked: Vec::new(),
+      removed: Vec::new(),
+      rounds: 0,
+      observers,
+    }
+  }
+  fn add_player(&mut self, player: Player, strategy: Box<dyn PlayStrategy>) {
+    self.strategies.insert(player.id, strategy);
+    self.players.push(player);
+  }
+  /// Tells `observer` about every step of the rounds from now on.
+  fn add_observer(&mut self, observer: Box<dyn Observer>) {
+    self.observers.add(observer);
+  }
+  /// Describes which strategies are seated at the table, e.g.
+  /// "stand-on(17) vs 1x deck-aware, 3x fixed(6)"
+  fn strategy_mix(&self) -> String {
+    let mut counts = HashMap::<String, u32>::new();
+    for strategy in self.strategies.values() {
+      *counts.entry(strategy.name()).or_insert(0) += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort();
+    let players = counts
+      .iter()
+      .map(|(name, count)| format!("{count}x {name}"))
+      .collect::<Vec<_>>()
+      .join(", ");
+    format!("{} vs {}", self.bank_strategy.name(), players)
+  }
+  /// Lets players place their bid, limited by what they and the bank can cover
+  fn place_bids(&mut self) {
+    for player in &mut self.players {
+      let strategy = self.strategies.get_mut(&player.id).unwrap();
+      let view = PlayerView {
+        hand: &player.hand,
+        bank_card: open_bank_card(&self.bank),
+        deck: &self.deck,
+        rules: &self.rules,
+        bank_stands_on: self.bank_strategy.stands_on(),
+      };
+      let mut placed_bid = strategy.bid(player, &view).min(player.money);
+      while self.rules.cover(placed_bid) > self.bank.money {
+        placed_bid -= 1;
       }
+      player.bid = placed_bid;
+      player.money -= placed_bid;
+      self.bank.money -= self.rules.cover(placed_bid);
+      self.observers.emit(Event::BidPlaced {
+        player: player.id,
+        bid: placed_bid,
+      });
     }
   }
   fn clean_up(&mut self) {
-    self.players.iter_mut().for_each(|p| p.hand.cards.clear());
+    self.players.iter_mut().for_each(|p| p.hand.clear());
     self.players.iter_mut().for_each(|p| p.money = 100);
 
-    self.bank.hand.cards.clear();
-    self.bank.money = 100;
-    self.deck = Deck::new();
-    self.deck.shuffle();
+    self.bank.hand.clear();
+    if self.rules.cover(1) > self.bank.money {
+      self.bank_broke();
+    }
+    self.fresh_deck().expect("The deck fit the stack before");
+  }
+  /// Deals `values` first every round, in the order the variant deals its
+  /// cards, with the rest of the deck shuffled below them.
+  fn stack(&mut self, values: Vec<Value>) -> Result<(), String> {
+    self.stacked = values;
+    self.fresh_deck()
+  }
+  /// Takes `values` out of the deck every round, as cards known to be gone.
+  fn remove(&mut self, values: Vec<Value>) -> Result<(), String> {
+    self.removed = values;
+    self.fresh_deck()
+  }
+  /// Puts every card back, then takes out the removed cards and stacks the
+  /// stacked ones.
+  fn fresh_deck(&mut self) -> Result<(), String> {
+    self.deck.reset();
+    self.deck.remove(&self.removed)?;
+    self.deck.stack(&self.stacked)
+  }
+  /// The bank can no longer cover any bid, so its tenure ends and a new bank
+  /// takes over with a fresh bankroll.
+  fn bank_broke(&mut self) {
+    self.observers.emit(Event::BankBroke {
+      rounds: self.bank_rounds_played,
+    });
+    self.bank_rounds_played = 0;
+    self.bank.money = self.bank_bankroll;
   }
 }
-
-fn generate_bid(player: &Player) -> u32 {
-  let mut bid = 1;
-  bid
+/// Pays out a resolved hand, returning the bid and any winnings to the
+/// player or the bid and the set aside cover to the bank. A push hands both
+/// back. Returns what the player won or lost.
+fn settle(rules: &Rules, bank: &mut Player, player: &mut Player, outcome: Outcome) -> i64 {
+  let cover = rules.cover(player.bid);
+  let winnings = rules.winnings(outcome, player.bid);
+  let net = if outcome.is_win() {
+    player.money += player.bid + winnings;
+    bank.money += cover - winnings;
+    winnings as i64
+  } else if outcome == Outcome::Push {
+    player.money += player.bid;
+    bank.money += cover;
+    0
+  } else if outcome == Outcome::Surrender {
+    let refund = player.bid / 2;
+    player.money += refund;
+    bank.money += cover + player.bid - refund;
+    -((player.bid - refund) as i64)
+  } else {
+    bank.money += cover + player.bid;
+    -(player.bid as i64)
+  };
+  player.bid = 0;
+  net
 }
+
 #[derive(Clone)]
 struct Player {
   id: u32,
@@ -556,67 +953,118 @@ struct Player {
 impl Player {
   fn new(id: u32) -> Player {
     Player {
-      id: id,
+      id,
       money: 100,
       bid: 0,
       hand: Hand::new(),
     }
   }
 }
-#[derive(Clone)]
+/// Most cards a hand can hold: 21 cards worth 1 and the card that busts it.
+const MAX_CARDS: usize = 22;
+
+/// The cards of a hand, kept inline with its totals so that dealing and
+/// evaluating hands doesn't allocate.
+#[derive(Clone, Copy)]
 struct Hand {
-  cards: Vec<Card>,
+  cards: [Card; MAX_CARDS],
+  len: u8,
+  /// Bitmask of the totals of 21 or less the cards can make, bit n meaning n
+  totals: u32,
+  /// Total with every card at its lowest worth
+  lowest: u32,
 }
 
 impl Hand {
   fn new() -> Hand {
-    Hand { cards: Vec::new() }
+    Hand {
+      cards: [Card::new(Suit::Spades, Value::Ace); MAX_CARDS],
+      len: 0,
+      totals: 1,
+      lowest: 0,
+    }
   }
 
-  fn add_card(&mut self, card: Card) {
-    self.cards.push(card);
+  fn cards(&self) -> &[Card] {
+    &self.cards[..self.len as usize]
   }
 
-  fn possible_values(&self) -> Vec<u32> {
-    let mut values: Vec<u32> = Vec::new();
-    let mut aces = 0;
-    let mut total = 0;
-    for card in &self
-      .cards
-      .iter()
-      .filter(|c| c.visibility != Visibility::None)
-      .collect::<Vec<_>>()
-    {
-      match card.value {
-        Value::Ace => aces += 1,
-        _ => total += card.worth(),
-      }
+  /// Adds a card, updating the totals with what it is worth in `worth`.
+  fn add_card(&mut self, card: Card, worth: &WorthScheme) {
+    self.cards[self.len as usize] = card;
+    self.len += 1;
+    let worths = worth.worths(card.value());
+    let mut totals = 0u64;
+    let mut remaining = worths;
+    while remaining != 0 {
+      totals |= (self.totals as u64) << remaining.trailing_zeros();
+      remaining &= remaining - 1;
     }
-    values.push(total);
-    for _ in 0..aces {
-      for i in 0..values.len() {
-        if values[i] + 11 <= 21 {
-          values.push(values[i] + 1);
-          values[i] += 11;
-        } else {
-          values[i] += 1;
-        }
-      }
+    self.totals = (totals & ((1 << 22) - 1)) as u32;
+    self.lowest += worths.trailing_zeros();
+  }
+
+  fn clear(&mut self) {
+    self.len = 0;
+    self.totals = 1;
+    self.lowest = 0;
+  }
+
+  fn open_all(&mut self) {
+    self.cards[..self.len as usize]
+      .iter_mut()
+      .for_each(|card| card.open());
+  }
+
+  /// All totals of 21 or less the cards can make, from high to low. If every
+  /// total is over 21 only the smallest one is returned.
+  fn possible_values(&self) -> Vec<u32> {
+    let mut values: Vec<u32> = (0..=21)
+      .rev()
+      .filter(|total| self.can_make(*total))
+      .collect();
+    if values.is_empty() {
+      values.push(self.lowest);
     }
     values
-    // .iter()
-    // .filter(|v| v.clone() <= &21u32)
-    // .map(|v| v.clone())
-    // .collect()
   }
+  /// The highest total of 21 or less, `None` once the hand is bust
+  fn best_total(&self) -> Option<u32> {
+    match self.totals {
+      0 => None,
+      totals => Some(31 - totals.leading_zeros()),
+    }
+  }
+  /// The best total, or the smallest one once the hand is bust
   fn greatest_value(&self) -> u32 {
-    self.possible_values().iter().max().unwrap().clone()
+    self.best_total().unwrap_or(self.lowest)
   }
   fn smallest_value(&self) -> u32 {
-    self.possible_values().iter().min().unwrap().clone()
+    self.lowest
+  }
+  /// The hand has more than one total of 21 or less, like an Ace counted as 11
+  fn is_soft(&self) -> bool {
+    self.totals.count_ones() > 1
+  }
+  /// Every total is over 21
+  fn is_bust(&self) -> bool {
+    self.totals == 0
+  }
+  /// 21 with the first two cards
+  fn is_natural(&self) -> bool {
+    self.len == 2 && self.best_total() == Some(21)
+  }
+  /// Reached `charlie_cards` cards without going bust
+  fn is_charlie(&self, charlie_cards: Option<usize>) -> bool {
+    !self.is_bust() && charlie_cards.is_some_and(|cards| self.cards().len() >= cards)
+  }
+  /// One way of counting the cards adds up to `total`, which is 21 or less
+  fn can_make(&self, total: u32) -> bool {
+    self.totals & 1 << total != 0
   }
 }
 
+#[allow(dead_code)]
 #[derive(PartialEq, Clone, Copy)]
 enum Visibility {
   All,
@@ -648,9 +1096,26 @@ enum Value {
   Jack,
   Queen,
   King,
+  Joker,
 }
 impl Value {
-  fn to_u32(&self) -> u32 {
+  const ALL: [Value; 13] = [
+    Value::Ace,
+    Value::Two,
+    Value::Three,
+    Value::Four,
+    Value::Five,
+    Value::Six,
+    Value::Seven,
+    Value::Eight,
+    Value::Nine,
+    Value::Ten,
+    Value::Jack,
+    Value::Queen,
+    Value::King,
+  ];
+
+  fn to_u32(self) -> u32 {
     match self {
       Value::Ace => 1,
       Value::Two => 2,
@@ -665,50 +1130,61 @@ impl Value {
       Value::Jack => 11,
       Value::Queen => 12,
       Value::King => 13,
+      Value::Joker => 14,
+    }
+  }
+
+  fn from_u32(value: u32) -> Value {
+    match value {
+      1 => Value::Ace,
+      2 => Value::Two,
+      3 => Value::Three,
+      4 => Value::Four,
+      5 => Value::Five,
+      6 => Value::Six,
+      7 => Value::Seven,
+      8 => Value::Eight,
+      9 => Value::Nine,
+      10 => Value::Ten,
+      11 => Value::Jack,
+      12 => Value::Queen,
+      13 => Value::King,
+      _ => Value::Joker,
     }
   }
 }
 
-#[derive(Clone)]
+/// A card packed in a byte, the value above the two suit bits, next to who
+/// can see it.
+#[derive(Clone, Copy)]
 struct Card {
-  suit: Suit,
-  value: Value,
+  code: u8,
   visibility: Visibility,
 }
 
 impl Card {
   fn new(suit: Suit, value: Value) -> Card {
     Card {
-      suit: suit,
-      value: value,
+      code: (value.to_u32() as u8) << 2 | suit as u8,
       visibility: Visibility::None,
     }
   }
 
-  fn open(&mut self) {
-    self.visibility = Visibility::All;
+  fn value(self) -> Value {
+    Value::from_u32((self.code >> 2) as u32)
   }
 
-  fn open_for_owner(&mut self) {
-    self.visibility = Visibility::Owner;
+  fn suit(self) -> Suit {
+    match self.code & 3 {
+      0 => Suit::Spades,
+      1 => Suit::Hearts,
+      2 => Suit::Diamonds,
+      _ => Suit::Clubs,
+    }
   }
 
-  fn worth(&self) -> u32 {
-    match self.value {
-      Value::Ace => 1,
-      Value::Two => 2,
-      Value::Three => 3,
-      Value::Four => 4,
-      Value::Five => 5,
-      Value::Six => 6,
-      Value::Seven => 7,
-      Value::Eight => 8,
-      Value::Nine => 9,
-      Value::Ten => 10,
-      Value::Jack => 10,
-      Value::Queen => 10,
-      Value::King => 10,
-    }
+  fn open(&mut self) {
+    self.visibility = Visibility::All;
   }
 }
 
@@ -739,42 +1215,76 @@ impl fmt::Display for Value {
       Value::Jack => " J",
       Value::Queen => " Q",
       Value::King => " K",
+      Value::Joker => "JK",
     };
     write!(f, "{}", value)
   }
 }
+impl std::str::FromStr for Value {
+  type Err = String;
+
+  fn from_str(value: &str) -> Result<Value, String> {
+    match value {
+      "A" => Ok(Value::Ace),
+      "2" => Ok(Value::Two),
+      "3" => Ok(Value::Three),
+      "4" => Ok(Value::Four),
+      "5" => Ok(Value::Five),
+      "6" => Ok(Value::Six),
+      "7" => Ok(Value::Seven),
+      "8" => Ok(Value::Eight),
+      "9" => Ok(Value::Nine),
+      "10" => Ok(Value::Ten),
+      "J" => Ok(Value::Jack),
+      "Q" => Ok(Value::Queen),
+      "K" => Ok(Value::King),
+      "JK" => Ok(Value::Joker),
+      _ => Err(format!("Unknown card value '{value}'")),
+    }
+  }
+}
 impl fmt::Display for Card {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     let visibility = match self.visibility {
       Visibility::None => " XX".to_string(),
-      _ => format!("{}{}", self.value, self.suit),
+      _ if self.value() == Value::Joker => " JK".to_string(),
+      _ => format!("{}{}", self.value(), self.suit()),
     };
     write!(f, "{}", visibility)
   }
 }
 impl fmt::Display for Hand {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    // The totals would give away a closed card
+    let hidden = self
+      .cards()
+      .iter()
+      .any(|card| card.visibility == Visibility::None);
+    let values = match hidden {
+      true => "?".to_string(),
+      false => self
+        .possible_values()
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(", "),
+    };
     write!(
       f,
       "{} ({})",
       &self
-        .cards
+        .cards()
         .iter()
         .map(|c| format!("{c}"))
         .collect::<Vec<_>>()
         .join(" "),
-      self
-        .possible_values()
-        .iter()
-        .map(|value| value.to_string())
-        .collect::<Vec<_>>()
-        .join(", ")
+      values
     )
   }
 }
 impl fmt::Display for Player {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    let hand = match self.hand.cards.len() {
+    let hand = match self.hand.cards().len() {
       0 => "no cards".to_string(),
       _ => format!("{}", self.hand),
     };
@@ -798,3 +1308,462 @@ impl fmt::Display for Game {
     write!(f, "Bank:\n  {}\nPlayers:\n{}", self.bank, players)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn hand(values: &[Value], worth: &WorthScheme) -> Hand {
+    let mut hand = Hand::new();
+    for value in values {
+      hand.add_card(Card::new(Suit::Spades, *value), worth);
+    }
+    hand
+  }
+
+  /// Every total the cards can make, trying each worth of every card
+  fn brute_force_totals(values: &[Value], worth: &WorthScheme) -> Vec<u32> {
+    let mut totals = vec![0];
+    for value in values {
+      let worths = worth.worths(*value);
+      totals = totals
+        .iter()
+        .flat_map(|total| {
+          (0..32)
+            .filter(move |w| worths & 1 << w != 0)
+            .map(move |w| total + w)
+        })
+        .collect();
+    }
+    totals.sort_unstable();
+    totals.dedup();
+    totals
+  }
+
+  /// Zero to six Aces with up to three other cards, the Aces both before and
+  /// after the other cards.
+  fn ace_hands() -> Vec<Vec<Value>> {
+    let others = &Value::ALL[1..];
+    let mut rests: Vec<Vec<Value>> = vec![Vec::new()];
+    for a in 0..others.len() {
+      rests.push(vec![others[a]]);
+      for b in a..others.len() {
+        rests.push(vec![others[a], others[b]]);
+        for c in b..others.len() {
+          rests.push(vec![others[a], others[b], others[c]]);
+        }
+      }
+    }
+    let mut hands = Vec::new();
+    for aces in 0..=6 {
+      for rest in &rests {
+        let mut aces_first = vec![Value::Ace; aces];
+        aces_first.extend(rest);
+        let mut aces_last = rest.clone();
+        aces_last.extend(vec![Value::Ace; aces]);
+        hands.push(aces_first);
+        hands.push(aces_last);
+      }
+    }
+    hands
+  }
+
+  #[test]
+  fn hand_evaluation_matches_every_ace_combination() {
+    for worth in [
+      WorthScheme::standard(),
+      WorthScheme::dutch(),
+      WorthScheme::german(),
+    ] {
+      for values in ace_hands() {
+        let hand = hand(&values, &worth);
+        let totals = brute_force_totals(&values, &worth);
+        let under: Vec<u32> = totals.iter().rev().copied().filter(|t| *t <= 21).collect();
+        let context = format!("{hand} with {worth}");
+
+        assert_eq!(hand.best_total(), under.first().copied(), "{context}");
+        assert_eq!(hand.is_bust(), under.is_empty(), "{context}");
+        assert_eq!(hand.is_soft(), under.len() > 1, "{context}");
+        assert_eq!(hand.smallest_value(), totals[0], "{context}");
+        assert_eq!(
+          hand.greatest_value(),
+          under.first().copied().unwrap_or(totals[0]),
+          "{context}"
+        );
+        match under.is_empty() {
+          true => assert_eq!(hand.possible_values(), vec![totals[0]], "{context}"),
+          false => assert_eq!(hand.possible_values(), under, "{context}"),
+        }
+        assert_eq!(
+          hand.is_natural(),
+          values.len() == 2 && under.first() == Some(&21),
+          "{context}"
+        );
+        assert_eq!(
+          hand.is_charlie(Some(5)),
+          values.len() >= 5 && !under.is_empty(),
+          "{context}"
+        );
+        assert!(!hand.is_charlie(None), "{context}");
+      }
+    }
+  }
+
+  #[test]
+  fn hand_evaluation_examples() {
+    let standard = WorthScheme::standard();
+    let ace_king = hand(&[Value::Ace, Value::King], &standard);
+    assert!(ace_king.is_natural());
+    assert!(ace_king.is_soft());
+
+    let aces = hand(&[Value::Ace, Value::Ace], &standard);
+    assert_eq!(aces.best_total(), Some(12));
+    assert_eq!(aces.possible_values(), vec![12, 2]);
+    assert!(!aces.is_natural());
+
+    let soft_17 = hand(&[Value::Ace, Value::Six], &standard);
+    assert!(soft_17.is_soft());
+    let hard_17 = hand(&[Value::Ace, Value::Six, Value::Ten], &standard);
+    assert_eq!(hard_17.best_total(), Some(17));
+    assert!(!hard_17.is_soft());
+
+    let made_21 = hand(&[Value::Seven, Value::Seven, Value::Seven], &standard);
+    assert_eq!(made_21.best_total(), Some(21));
+    assert!(!made_21.is_natural());
+
+    let bust = hand(&[Value::King, Value::Queen, Value::Two], &standard);
+    assert!(bust.is_bust());
+    assert_eq!(bust.best_total(), None);
+    assert_eq!(bust.greatest_value(), 22);
+
+    // Dutch faces count 2, 3 and 4, so seven small cards stay under 21
+    let dutch = WorthScheme::dutch();
+    let charlie = hand(
+      &[
+        Value::Jack,
+        Value::Jack,
+        Value::Queen,
+        Value::Queen,
+        Value::King,
+        Value::Two,
+        Value::Ace,
+      ],
+      &dutch,
+    );
+    assert_eq!(charlie.best_total(), Some(17));
+    assert!(charlie.is_charlie(Some(7)));
+    assert!(!charlie.is_charlie(Some(8)));
+  }
+
+  /// Calls `check` with every multiset of `size` values from `values`.
+  fn multisets(
+    values: &[Value],
+    size: usize,
+    hand: &mut Vec<Value>,
+    check: &mut impl FnMut(&[Value]),
+  ) {
+    if size == 0 {
+      check(hand);
+      return;
+    }
+    for (index, value) in values.iter().enumerate() {
+      hand.push(*value);
+      multisets(&values[index..], size - 1, hand, check);
+      hand.pop();
+    }
+  }
+
+  #[test]
+  fn possible_values_of_every_multiset_up_to_seven_cards() {
+    for worth in [WorthScheme::standard(), WorthScheme::dutch()] {
+      for size in 0..=7 {
+        multisets(&Value::ALL, size, &mut Vec::new(), &mut |values| {
+          let totals = brute_force_totals(values, &worth);
+          let mut expected: Vec<u32> = totals.iter().rev().copied().filter(|t| *t <= 21).collect();
+          if expected.is_empty() {
+            expected.push(totals[0]);
+          }
+          let hand = hand(values, &worth);
+          assert_eq!(hand.possible_values(), expected, "{hand} with {worth}");
+        });
+      }
+    }
+  }
+
+  #[test]
+  fn fresh_deck_has_52_unique_cards() {
+    let mut deck = Deck::new(
+      &DeckComposition::french(),
+      &WorthScheme::standard(),
+      StdRng::seed_from_u64(1),
+    );
+    for _ in 0..2 {
+      let mut codes: Vec<u8> = deck.cards.iter().map(|card| card.code).collect();
+      codes.sort_unstable();
+      codes.dedup();
+      assert_eq!(deck.cards.len(), 52);
+      assert_eq!(codes.len(), 52);
+      for value in Value::ALL {
+        let count = deck
+          .cards
+          .iter()
+          .filter(|card| card.value() == value)
+          .count();
+        assert_eq!(count, 4, "{value}");
+      }
+      // Starting over puts back whatever was drawn or discarded
+      let mut hand = Hand::new();
+      deck.deal(&mut hand, Visibility::All).unwrap();
+      deck.deal(&mut hand, Visibility::All).unwrap();
+      deck.discard(&mut hand);
+      deck.draw().unwrap();
+      deck.reset();
+    }
+  }
+
+  #[test]
+  fn stacking_leaves_the_rest_shuffled() {
+    let mut deck = Deck::new(
+      &DeckComposition::french(),
+      &WorthScheme::standard(),
+      StdRng::seed_from_u64(7),
+    );
+    // The card under a stacked Seven is one of the three Sevens left as
+    // often as any other card
+    let mut sevens = 0;
+    for _ in 0..10_000 {
+      deck.reset();
+      deck.stack(&[Value::Seven]).unwrap();
+      deck.draw().unwrap();
+      if deck.draw().unwrap().value() == Value::Seven {
+        sevens += 1;
+      }
+    }
+    assert!((400..800).contains(&sevens), "{sevens} Sevens");
+  }
+
+  #[test]
+  fn removing_and_stacking_keep_the_shuffle() {
+    let mut game = game(&[], &["fixed-6"]);
+    let order =
+      |game: &Game| -> Vec<Value> { game.deck.cards.iter().map(|card| card.value()).collect() };
+    game.deck.reset();
+    let shuffled = order(&game);
+    game.deck.remove(&[]).unwrap();
+    game.deck.stack(&[]).unwrap();
+    assert!(order(&game) == shuffled);
+
+    // Taking out a Seven leaves the other cards in the order they were
+    game.deck.remove(&[Value::Seven]).unwrap();
+    let left = order(&game);
+    let taken = (0..left.len())
+      .find(|index| left[*index] != shuffled[*index])
+      .unwrap_or(left.len());
+    assert!(shuffled[taken] == Value::Seven);
+    assert!(left[..taken] == shuffled[..taken] && left[taken..] == shuffled[taken + 1..]);
+  }
+
+  #[test]
+  fn decks_are_checked_for_every_seat() {
+    // Aces to 5s, 20 cards
+    let mut rules = Rules::default();
+    for value in ["6", "7", "8", "9", "10", "J", "Q", "K"] {
+      rules.set(&format!("copies-{value}"), "0").unwrap();
+    }
+    assert!(rules.check_deck(1).is_ok());
+    assert!(rules.check_deck(4).is_err());
+  }
+
+  #[test]
+  fn decks_too_small_for_a_round_are_refused() {
+    let mut rules = Rules::default();
+    for value in ["3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K"] {
+      rules.set(&format!("copies-{value}"), "0").unwrap();
+    }
+    assert!(rules.check_deck(1).is_err());
+    assert!(Rules::default().check_deck(1).is_ok());
+
+    // Dealt anyway, the round fails instead of panicking
+    let mut game = game(&[], &["fixed-0"]);
+    game.rules = rules.clone();
+    game.deck = Deck::new(&rules.deck, &rules.worth, StdRng::seed_from_u64(21));
+    game.fresh_deck().unwrap();
+    assert!(game.play_round().is_err());
+  }
+
+  /// A seeded game with a bank and a player per strategy, playing `rules`
+  /// in order. A variant has to come first.
+  fn game(rules: &[&str], strategies: &[&str]) -> Game {
+    let mut game_rules = Rules::default();
+    for rule in rules {
+      let (key, value) = rule.split_once('=').unwrap();
+      game_rules.set(key, value).unwrap();
+    }
+    let mut game = Game::new(
+      Player::new(0),
+      Box::new(StandOn::new(17)),
+      game_rules,
+      StdRng::seed_from_u64(21),
+    );
+    for (index, name) in strategies.iter().enumerate() {
+      let strategy = play_strategy_by_name(name).unwrap();
+      game.add_player(Player::new(index as u32 + 1), strategy);
+    }
+    game
+  }
+
+  fn money(game: &Game) -> u32 {
+    game.bank.money + game.players.iter().map(|player| player.money).sum::<u32>()
+  }
+
+  fn outcome_count(game: &Game, outcome: Outcome) -> u32 {
+    game
+      .records
+      .borrow()
+      .outcomes
+      .get(&outcome)
+      .map_or(0, |(count, _)| *count)
+  }
+
+  #[test]
+  fn late_surrender_needs_a_peek() {
+    for (peek, surrenders) in [("peek=yes", true), ("peek=no", false)] {
+      let mut game = game(&["variant=casino", "surrender=late", peek], &["basic"]);
+      for _ in 0..5_000 {
+        game.play_round().unwrap();
+        game.clean_up();
+      }
+      assert_eq!(
+        outcome_count(&game, Outcome::Surrender) > 0,
+        surrenders,
+        "{peek}"
+      );
+    }
+  }
+
+  #[test]
+  fn money_is_conserved_every_round() {
+    let strategies = ["basic", "deck-aware", "fixed-6", "fixed-4"];
+    for variant in ["variant=dutch", "variant=casino", "variant=pontoon"] {
+      let mut game = game(
+        &[variant, "insurance=yes", "redeal-choice=player"],
+        &strategies,
+      );
+      for _ in 0..20_000 {
+        let before = money(&game);
+        game.play_round().unwrap();
+        assert_eq!(money(&game), before, "{variant}");
+        assert!(game.players.iter().all(|player| player.bid == 0));
+        game.clean_up();
+      }
+    }
+  }
+
+  #[test]
+  fn seven_and_eight_are_dealt_again() {
+    let mut game = game(&[], &["fixed-6"]);
+    // Player open, bank open, player closed, the redeal, bank closed
+    game
+      .stack(vec![
+        Value::Seven,
+        Value::Five,
+        Value::Eight,
+        Value::Ten,
+        Value::Nine,
+        Value::Six,
+      ])
+      .unwrap();
+    let variant = game.rules.variant;
+    variant.deal(&mut game).unwrap();
+
+    let player = &game.players[0];
+    let values: Vec<Value> = player
+      .hand
+      .cards()
+      .iter()
+      .map(|card| card.value())
+      .collect();
+    assert!(values == [Value::Ten, Value::Nine]);
+    let discarded: Vec<Value> = game.deck.discards.iter().map(|card| card.value()).collect();
+    assert!(discarded == [Value::Seven, Value::Eight]);
+    let trigger = RedealTrigger::Cards(Value::Seven, Value::Eight);
+    assert_eq!(game.records.borrow().redeals[&trigger], (1, 1));
+  }
+
+  #[test]
+  fn redeals_end_when_every_hand_triggers_one() {
+    let triggers: Vec<String> = (2..=22).map(|total| format!("total-{total}")).collect();
+    let redeal = format!("redeal={}", triggers.join(","));
+    let mut game = game(&[&redeal, "redeal-choice=forced"], &["fixed-0"]);
+    game.play_round().unwrap();
+
+    let records = game.records.borrow();
+    assert!(records
+      .redeals
+      .values()
+      .all(|(offered, taken)| offered == taken));
+    assert!(records.redealt.contains(&1));
+  }
+
+  #[test]
+  fn more_than_six_cards_win() {
+    let mut game = game(&[], &["fixed-0"]);
+    // Player open, bank open, player closed, bank closed and five hits
+    game
+      .stack(vec![
+        Value::Two,
+        Value::Ten,
+        Value::Two,
+        Value::Seven,
+        Value::Two,
+        Value::Three,
+        Value::Three,
+        Value::Ace,
+        Value::Two,
+      ])
+      .unwrap();
+    let bid = 1;
+    game.play_round().unwrap();
+
+    assert_eq!(outcome_count(&game, Outcome::Charlie), 1);
+    let winnings = game.rules.winnings(Outcome::Charlie, bid);
+    assert_eq!(game.players[0].money, 100 + winnings);
+  }
+
+  /// Plays a round where a player standing on `player` meets a bank that is
+  /// dealt `bank` and draws `draws`, returning the player's outcome.
+  fn bank_round(player: [Value; 2], bank: [Value; 2], draws: &[Value]) -> Outcome {
+    let mut game = game(&[], &["fixed-0"]);
+    // Stand on anything
+    game
+      .strategies
+      .insert(1, Box::new(strategy::FixedThreshold::new(22.0)));
+    let mut stack = vec![player[0], bank[0], player[1], bank[1]];
+    stack.extend(draws);
+    game.stack(stack).unwrap();
+    game.play_round().unwrap();
+    let outcomes: Vec<_> = Outcome::ALL
+      .into_iter()
+      .filter(|outcome| outcome_count(&game, *outcome) == 1)
+      .collect();
+    assert_eq!(outcomes.len(), 1);
+    assert_eq!(game.bank.hand.cards().len(), 2 + draws.len());
+    outcomes[0]
+  }
+
+  #[test]
+  fn bank_draws_to_its_threshold_and_resolves() {
+    use Value::*;
+    // The bank stands on 17 or more
+    assert_eq!(bank_round([Ten, Nine], [Ten, Seven], &[]), Outcome::Win);
+    assert_eq!(bank_round([Ten, Six], [Ten, Seven], &[]), Outcome::Loss);
+    // and wins ties under the default rules
+    assert_eq!(bank_round([Ten, Seven], [Ten, Seven], &[]), Outcome::Loss);
+    // It draws below 17
+    assert_eq!(
+      bank_round([Ten, Nine], [Ten, Four], &[Two, Five]),
+      Outcome::Loss
+    );
+    assert_eq!(bank_round([Ten, Two], [Ten, Six], &[King]), Outcome::Win);
+  }
+}
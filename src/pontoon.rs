@@ -0,0 +1,102 @@
+//! British Pontoon. All cards are dealt closed and players bid after seeing
+//! their first card. A player can buy a closed card for another stake or twist
+//! an open one for free, but may not stick below 15. A pontoon or a five card
+//! trick pays double and the bank wins ties.
+
+use std::fmt;
+
+use crate::events::{Event, Seat};
+use crate::rules::{Outcome, Rules};
+use crate::strategy::{Action, Actions};
+use crate::variant::{SeatHand, Variant};
+use crate::{Game, Player, Visibility};
+
+pub struct Pontoon;
+
+impl Variant for Pontoon {
+  fn name(&self) -> &'static str {
+    "Pontoon"
+  }
+
+  fn rules(&self) -> Rules {
+    Rules::pontoon()
+  }
+
+  fn deal(&self, game: &mut Game) -> Result<(), String> {
+    for round in 0..2 {
+      for player in &mut game.players {
+        let card = game.deck.deal(&mut player.hand, Visibility::Owner)?;
+        game
+          .observers
+          .emit(Event::dealt(Seat::player(player.id), card));
+      }
+      let card = game.deck.deal(&mut game.bank.hand, Visibility::Owner)?;
+      game.observers.emit(Event::dealt(Seat::Bank, card));
+      if round == 0 {
+        game.place_bids();
+      }
+    }
+    for player in &mut game.players {
+      if player.bid == 0 {
+        player.hand.clear();
+      }
+    }
+    Ok(())
+  }
+
+  fn settle_early(&self, rules: &Rules, seat: &SeatHand) -> Option<Outcome> {
+    let hand = &seat.hand;
+    if hand.is_bust() {
+      Some(Outcome::Bust)
+    } else if hand.is_charlie(rules.charlie_cards) {
+      Some(Outcome::Charlie)
+    } else {
+      None
+    }
+  }
+
+  fn allowed_actions(
+    &self,
+    rules: &Rules,
+    bank: &Player,
+    player: &Player,
+    hands: &[SeatHand],
+    current: usize,
+  ) -> Actions {
+    let seat = &hands[current];
+    if seat.is_natural() {
+      return Actions::from([Action::Stand]);
+    }
+    let mut allowed = Actions::from([Action::Hit]);
+    if seat.hand.greatest_value() >= rules.min_stand {
+      allowed.push(Action::Stand);
+    }
+    if !seat.twisted
+      && seat.hand.cards().len() < rules.buy_cards
+      && seat.can_raise(rules, bank, player, seat.stake)
+    {
+      allowed.push(Action::Buy);
+    }
+    if let [first, second] = seat.hand.cards() {
+      if first.value() == second.value()
+        && hands.len() < rules.split_hands
+        && seat.can_split(rules, bank, player)
+      {
+        allowed.push(Action::Split);
+      }
+    }
+    allowed
+  }
+
+  fn fmt_rules(&self, rules: &Rules, f: &mut fmt::Formatter) -> fmt::Result {
+    writeln!(f, "Players stick on {} or more", rules.min_stand)?;
+    match rules.buy_cards {
+      0 => writeln!(f, "No buying")?,
+      cards => writeln!(f, "Buy closed cards up to {cards} cards in hand")?,
+    }
+    match rules.split_hands {
+      1 => write!(f, "No splitting"),
+      hands => write!(f, "Split up to {hands} hands"),
+    }
+  }
+}
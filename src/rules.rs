@@ -0,0 +1,719 @@
+use std::fmt;
+
+use crate::casino::Casino;
+use crate::dutch::Dutch;
+use crate::pontoon::Pontoon;
+use crate::variant::{variant_by_name, Variant};
+use crate::{Card, Hand, Value};
+
+/// How a player's hand ended up after the bank resolved the round.
+#[derive(Eq, Hash, PartialEq, Clone, Copy, Debug)]
+pub enum Outcome {
+  /// 21 with the first two cards
+  Natural,
+  /// 21 with three or more cards
+  Made21,
+  /// Reached the charlie card count without busting
+  Charlie,
+  /// Stood on a higher total than the bank, or the bank busted
+  Win,
+  /// Went over 21
+  Bust,
+  /// Stood on the same total as the bank and got the bid back
+  Push,
+  /// Stood on a total that did not beat the bank
+  Loss,
+  /// Gave up the hand for half the bid back
+  Surrender,
+}
+
+impl Outcome {
+  pub const ALL: [Outcome; 8] = [
+    Outcome::Natural,
+    Outcome::Made21,
+    Outcome::Charlie,
+    Outcome::Win,
+    Outcome::Bust,
+    Outcome::Push,
+    Outcome::Loss,
+    Outcome::Surrender,
+  ];
+
+  pub fn is_win(self) -> bool {
+    !matches!(
+      self,
+      Outcome::Bust | Outcome::Push | Outcome::Loss | Outcome::Surrender
+    )
+  }
+}
+
+/// Which two card hands a casino player may double down on.
+#[derive(PartialEq, Clone, Copy)]
+pub enum DoubleRule {
+  Never,
+  /// Only on a total of 9, 10 or 11
+  NineToEleven,
+  Any,
+}
+
+impl DoubleRule {
+  fn parse(value: &str) -> Result<DoubleRule, String> {
+    match value {
+      "never" => Ok(DoubleRule::Never),
+      "9-11" => Ok(DoubleRule::NineToEleven),
+      "any" => Ok(DoubleRule::Any),
+      _ => Err(format!("Expected never, 9-11 or any, got '{value}'")),
+    }
+  }
+}
+
+/// When a casino player may give up their hand for half the bid.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Surrender {
+  Never,
+  /// After the bank checked for a natural, so only when it peeks
+  Late,
+  /// Before the bank checks for a natural, so also against one
+  Early,
+}
+
+impl Surrender {
+  fn parse(value: &str) -> Result<Surrender, String> {
+    match value {
+      "never" => Ok(Surrender::Never),
+      "late" => Ok(Surrender::Late),
+      "early" => Ok(Surrender::Early),
+      _ => Err(format!("Expected never, late or early, got '{value}'")),
+    }
+  }
+}
+
+/// Parses an on/off house rule.
+fn parse_flag(value: &str) -> Result<bool, String> {
+  match value {
+    "yes" | "on" | "true" => Ok(true),
+    "no" | "off" | "false" => Ok(false),
+    _ => Err(format!("Expected yes or no, got '{value}'")),
+  }
+}
+
+/// Who gets the hand when a player stands on the same total as the bank.
+#[derive(PartialEq, Clone, Copy)]
+pub enum TieRule {
+  Bank,
+  Push,
+  Player,
+}
+
+impl TieRule {
+  /// Outcome for a player that stood on the same total as the bank.
+  pub fn outcome(self) -> Outcome {
+    match self {
+      TieRule::Bank => Outcome::Loss,
+      TieRule::Push => Outcome::Push,
+      TieRule::Player => Outcome::Win,
+    }
+  }
+
+  fn parse(value: &str) -> Result<TieRule, String> {
+    match value {
+      "bank" => Ok(TieRule::Bank),
+      "push" => Ok(TieRule::Push),
+      "player" => Ok(TieRule::Player),
+      _ => Err(format!(
+        "Expected bank, push or player for ties, got '{value}'"
+      )),
+    }
+  }
+}
+
+/// What each card value counts for. A value with several worths, like the
+/// Ace, counts as whichever suits the hand best.
+#[derive(Clone)]
+pub struct WorthScheme {
+  pub name: String,
+  /// Bitmask of the worths of each value from Ace to King and the Joker, bit
+  /// n meaning the value can count as n
+  worths: [u32; 14],
+}
+
+impl WorthScheme {
+  /// A = 1 or 11, J/Q/K = 10
+  pub fn standard() -> WorthScheme {
+    WorthScheme::with_faces("standard", 10, 10, 10)
+  }
+
+  /// Dutch "eenentwintigen": A = 1 or 11, J = 2, Q = 3, K = 4
+  pub fn dutch() -> WorthScheme {
+    WorthScheme::with_faces("dutch", 2, 3, 4)
+  }
+
+  /// German "Siebzehn und Vier": A = 11, J = 2, Q = 3, K = 4
+  pub fn german() -> WorthScheme {
+    let mut scheme = WorthScheme::with_faces("german", 2, 3, 4);
+    scheme.set(Value::Ace, &[11]);
+    scheme
+  }
+
+  fn with_faces(name: &str, jack: u32, queen: u32, king: u32) -> WorthScheme {
+    let mut worths = [0; 14];
+    for (index, worth) in worths.iter_mut().enumerate().take(10) {
+      *worth = 1 << (index + 1);
+    }
+    worths[0] |= 1 << 11;
+    worths[10] = 1 << jack;
+    worths[11] = 1 << queen;
+    worths[12] = 1 << king;
+    // A joker is wild and counts as anything from 1 to 11
+    worths[13] = (1..=11).fold(0, |mask, worth| mask | 1 << worth);
+    WorthScheme {
+      name: name.to_string(),
+      worths,
+    }
+  }
+
+  /// Bitmask of what a value can count as.
+  pub fn worths(&self, value: Value) -> u32 {
+    self.worths[value.to_u32() as usize - 1]
+  }
+
+  /// The least a card of `value` can count as.
+  pub fn lowest(&self, value: Value) -> u32 {
+    self.worths(value).trailing_zeros()
+  }
+
+  pub fn set(&mut self, value: Value, worths: &[u32]) {
+    self.worths[value.to_u32() as usize - 1] =
+      worths.iter().fold(0, |mask, worth| mask | 1 << worth);
+  }
+
+  fn parse(value: &str) -> Result<WorthScheme, String> {
+    match value {
+      "standard" => Ok(WorthScheme::standard()),
+      "dutch" => Ok(WorthScheme::dutch()),
+      "german" => Ok(WorthScheme::german()),
+      _ => Err(format!("Expected standard, dutch or german, got '{value}'")),
+    }
+  }
+
+  /// Parses the worths of a single value, like `1/11`.
+  fn parse_worths(value: &str) -> Result<Vec<u32>, String> {
+    value
+      .split('/')
+      .map(|worth| match worth.parse() {
+        Ok(worth) if (1..32).contains(&worth) => Ok(worth),
+        _ => Err(format!("Invalid card worth '{worth}', expected 1 to 31")),
+      })
+      .collect()
+  }
+}
+
+/// Which cards make up the deck.
+#[derive(Clone)]
+pub struct DeckComposition {
+  pub name: String,
+  /// Copies of each value per suit, from Ace to King
+  copies: [u32; 13],
+  pub jokers: u32,
+  /// Number of these decks shuffled together
+  pub decks: u32,
+}
+
+impl DeckComposition {
+  /// The standard 52 card French deck
+  pub fn french() -> DeckComposition {
+    DeckComposition {
+      name: "french".to_string(),
+      copies: [1; 13],
+      jokers: 0,
+      decks: 1,
+    }
+  }
+
+  /// The 48 card Spanish 21 deck, a French deck without the tens
+  pub fn spanish() -> DeckComposition {
+    let mut deck = DeckComposition::french();
+    deck.name = "spanish".to_string();
+    deck.set(Value::Ten, 0);
+    deck
+  }
+
+  /// The 32 card piquet deck, a French deck from seven up
+  pub fn piquet() -> DeckComposition {
+    let mut deck = DeckComposition::french();
+    deck.name = "piquet".to_string();
+    for value in [
+      Value::Two,
+      Value::Three,
+      Value::Four,
+      Value::Five,
+      Value::Six,
+    ] {
+      deck.set(value, 0);
+    }
+    deck
+  }
+
+  /// Copies of a value per suit.
+  pub fn copies(&self, value: Value) -> u32 {
+    self.copies[value.to_u32() as usize - 1]
+  }
+
+  pub fn set(&mut self, value: Value, copies: u32) {
+    self.copies[value.to_u32() as usize - 1] = copies;
+  }
+
+  pub fn size(&self) -> u32 {
+    (self.copies.iter().sum::<u32>() * 4 + self.jokers) * self.decks
+  }
+
+  fn parse(value: &str) -> Result<DeckComposition, String> {
+    match value {
+      "french" => Ok(DeckComposition::french()),
+      "spanish" => Ok(DeckComposition::spanish()),
+      "piquet" => Ok(DeckComposition::piquet()),
+      _ => Err(format!("Expected french, spanish or piquet, got '{value}'")),
+    }
+  }
+}
+
+/// Starting hand that entitles the player to a fresh pair of cards.
+#[derive(Eq, Hash, PartialEq, Clone, Copy)]
+pub enum RedealTrigger {
+  /// These two values in either order, like the classic 7 and 8
+  Cards(Value, Value),
+  /// Any two cards of the same value
+  Pair,
+  /// Any two cards worth this much together
+  Total(u32),
+}
+
+impl RedealTrigger {
+  /// Totals count every card at its lowest worth in `worth`.
+  pub fn matches(&self, first: Card, second: Card, worth: &WorthScheme) -> bool {
+    let (first, second) = (first.value(), second.value());
+    match self {
+      RedealTrigger::Cards(a, b) => (first == *a && second == *b) || (first == *b && second == *a),
+      RedealTrigger::Pair => first == second,
+      RedealTrigger::Total(total) => worth.lowest(first) + worth.lowest(second) == *total,
+    }
+  }
+
+  fn parse(value: &str) -> Result<RedealTrigger, String> {
+    if value == "pair" {
+      return Ok(RedealTrigger::Pair);
+    }
+    if let Some(total) = value.strip_prefix("total-") {
+      let total = total
+        .parse()
+        .map_err(|_| format!("Invalid redeal total '{total}'"))?;
+      return Ok(RedealTrigger::Total(total));
+    }
+    let (a, b) = value.split_once('+').ok_or(format!(
+      "Expected a redeal like 7+8, pair or total-<n>, got '{value}'"
+    ))?;
+    Ok(RedealTrigger::Cards(a.parse()?, b.parse()?))
+  }
+}
+
+/// Winnings paid on top of the returned bid, as a ratio like 3:2.
+#[derive(PartialEq, Clone, Copy)]
+pub struct Payout {
+  pub numerator: u32,
+  pub denominator: u32,
+}
+
+impl Payout {
+  pub fn new(numerator: u32, denominator: u32) -> Payout {
+    Payout {
+      numerator,
+      denominator,
+    }
+  }
+
+  /// Winnings for a bid, rounded down like a casino would.
+  pub fn winnings(&self, bid: u32) -> u32 {
+    bid * self.numerator / self.denominator
+  }
+
+  fn parse(value: &str) -> Result<Payout, String> {
+    let (numerator, denominator) = value
+      .split_once(':')
+      .ok_or(format!("Expected a payout like 3:2, got '{value}'"))?;
+    let numerator = numerator
+      .parse()
+      .map_err(|_| format!("Invalid payout numerator '{numerator}'"))?;
+    let denominator = denominator
+      .parse()
+      .map_err(|_| format!("Invalid payout denominator '{denominator}'"))?;
+    if denominator == 0 {
+      return Err("Payout denominator can't be 0".to_string());
+    }
+    Ok(Payout::new(numerator, denominator))
+  }
+}
+
+/// House rules that can be changed with `--rule key=value`.
+#[derive(Clone)]
+pub struct Rules {
+  pub variant: &'static dyn Variant,
+  pub natural_payout: Payout,
+  pub made_21_payout: Payout,
+  pub charlie_payout: Payout,
+  pub win_payout: Payout,
+  /// Number of cards that wins the hand outright, if any
+  pub charlie_cards: Option<usize>,
+  pub tie: TieRule,
+  /// Starting hands that are dealt again
+  pub redeals: Vec<RedealTrigger>,
+  /// Players may keep a hand that triggers a redeal
+  pub redeal_optional: bool,
+  pub worth: WorthScheme,
+  pub deck: DeckComposition,
+  /// Most hands a casino player can split into, 1 disables splitting
+  pub split_hands: usize,
+  pub resplit_aces: bool,
+  /// Split Aces may draw more than the one card they are dealt
+  pub hit_split_aces: bool,
+  pub double: DoubleRule,
+  pub double_after_split: bool,
+  pub surrender: Surrender,
+  pub insurance: bool,
+  /// The bank checks its hole card for a natural before the players act.
+  /// Without a peek the bank gets its second card after the players, as in
+  /// European casinos, and a bank natural takes doubled and split bids too.
+  pub peek: bool,
+  pub bank_hits_soft_17: bool,
+  /// Lowest total a player may stand on
+  pub min_stand: u32,
+  /// Most cards a hand can have after buying a closed card, 0 disables buying
+  pub buy_cards: usize,
+}
+
+impl Default for Rules {
+  fn default() -> Rules {
+    Rules {
+      natural_payout: Payout::new(1, 1),
+      made_21_payout: Payout::new(1, 1),
+      charlie_payout: Payout::new(1, 1),
+      win_payout: Payout::new(1, 1),
+      variant: &Dutch,
+      charlie_cards: Some(7),
+      tie: TieRule::Bank,
+      redeals: vec![RedealTrigger::Cards(Value::Seven, Value::Eight)],
+      redeal_optional: false,
+      worth: WorthScheme::standard(),
+      deck: DeckComposition::french(),
+      split_hands: 1,
+      resplit_aces: false,
+      hit_split_aces: false,
+      double: DoubleRule::Never,
+      double_after_split: false,
+      surrender: Surrender::Never,
+      insurance: false,
+      peek: false,
+      bank_hits_soft_17: false,
+      min_stand: 0,
+      buy_cards: 0,
+    }
+  }
+}
+
+impl Rules {
+  /// Common casino blackjack rules: naturals pay 3:2, ties push, up to four
+  /// hands by splitting, doubling on any two cards and late surrender.
+  pub fn casino() -> Rules {
+    Rules {
+      variant: &Casino,
+      natural_payout: Payout::new(3, 2),
+      charlie_cards: None,
+      tie: TieRule::Push,
+      redeals: Vec::new(),
+      split_hands: 4,
+      double: DoubleRule::Any,
+      double_after_split: true,
+      surrender: Surrender::Late,
+      insurance: true,
+      peek: true,
+      ..Rules::default()
+    }
+  }
+
+  /// Pontoon rules: a pontoon or five card trick pays 2:1, ties go to the
+  /// bank, players stick on 15 or more and buy up to their fourth card.
+  pub fn pontoon() -> Rules {
+    Rules {
+      variant: &Pontoon,
+      natural_payout: Payout::new(2, 1),
+      charlie_cards: Some(5),
+      charlie_payout: Payout::new(2, 1),
+      redeals: Vec::new(),
+      split_hands: 4,
+      hit_split_aces: true,
+      peek: true,
+      min_stand: 15,
+      buy_cards: 4,
+      ..Rules::default()
+    }
+  }
+
+  /// Sets a rule. `variant` resets all other rules to the variant's defaults,
+  /// so it has to come first.
+  pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+    match key {
+      "variant" => *self = variant_by_name(value)?.rules(),
+      "natural" => self.natural_payout = Payout::parse(value)?,
+      "made-21" => self.made_21_payout = Payout::parse(value)?,
+      "charlie" => self.charlie_payout = Payout::parse(value)?,
+      "win" => self.win_payout = Payout::parse(value)?,
+      "charlie-cards" if value == "none" => self.charlie_cards = None,
+      "charlie-cards" => {
+        self.charlie_cards = match value.parse() {
+          Ok(cards) if cards >= 3 => Some(cards),
+          _ => return Err(format!("Invalid card count '{value}', expected 3 or more")),
+        }
+      }
+      "tie" => self.tie = TieRule::parse(value)?,
+      "redeal" if value == "none" => self.redeals.clear(),
+      "redeal" => {
+        self.redeals = value
+          .split(',')
+          .map(RedealTrigger::parse)
+          .collect::<Result<_, _>>()?
+      }
+      "redeal-choice" => {
+        self.redeal_optional = match value {
+          "forced" => false,
+          "player" => true,
+          _ => return Err(format!("Expected forced or player, got '{value}'")),
+        }
+      }
+      "worth" => self.worth = WorthScheme::parse(value)?,
+      _ if key.starts_with("worth-") => {
+        let card_value = key["worth-".len()..].parse()?;
+        let worths = WorthScheme::parse_worths(value)?;
+        self.worth.set(card_value, &worths);
+        self.worth.name = format!("custom {}", self.worth.name.trim_start_matches("custom "));
+      }
+      "deck" => self.deck = DeckComposition::parse(value)?,
+      "jokers" => {
+        self.deck.jokers = value
+          .parse()
+          .map_err(|_| format!("Invalid number of jokers '{value}'"))?
+      }
+      "decks" => {
+        self.deck.decks = match value.parse() {
+          Ok(decks) if decks > 0 => decks,
+          _ => return Err(format!("Invalid number of decks '{value}'")),
+        }
+      }
+      _ if key.starts_with("copies-") => {
+        let card_value: Value = key["copies-".len()..].parse()?;
+        if card_value == Value::Joker {
+          return Err("Use jokers=<n> to add jokers".to_string());
+        }
+        let copies = value
+          .parse()
+          .map_err(|_| format!("Invalid number of copies '{value}'"))?;
+        self.deck.set(card_value, copies);
+        self.deck.name = format!("custom {}", self.deck.name.trim_start_matches("custom "));
+      }
+      "split-hands" => {
+        self.split_hands = match value.parse() {
+          Ok(hands) if hands > 0 => hands,
+          _ => return Err(format!("Invalid number of split hands '{value}'")),
+        }
+      }
+      "resplit-aces" => self.resplit_aces = parse_flag(value)?,
+      "hit-split-aces" => self.hit_split_aces = parse_flag(value)?,
+      "double" => self.double = DoubleRule::parse(value)?,
+      "double-after-split" => self.double_after_split = parse_flag(value)?,
+      "surrender" => self.surrender = Surrender::parse(value)?,
+      "insurance" => self.insurance = parse_flag(value)?,
+      "peek" => self.peek = parse_flag(value)?,
+      "hit-soft-17" => self.bank_hits_soft_17 = parse_flag(value)?,
+      "min-stand" => {
+        self.min_stand = value
+          .parse()
+          .map_err(|_| format!("Invalid total '{value}'"))?
+      }
+      "buy-cards" => {
+        self.buy_cards = value
+          .parse()
+          .map_err(|_| format!("Invalid card count '{value}'"))?
+      }
+      _ => return Err(format!("Unknown rule '{key}'")),
+    }
+    Ok(())
+  }
+
+  /// Winnings paid on top of the returned bid, 0 if the outcome loses.
+  pub fn winnings(&self, outcome: Outcome, bid: u32) -> u32 {
+    match outcome {
+      Outcome::Natural => self.natural_payout.winnings(bid),
+      Outcome::Made21 => self.made_21_payout.winnings(bid),
+      Outcome::Charlie => self.charlie_payout.winnings(bid),
+      Outcome::Win => self.win_payout.winnings(bid),
+      Outcome::Bust | Outcome::Push | Outcome::Loss | Outcome::Surrender => 0,
+    }
+  }
+
+  /// What the player gains or loses per 1$ bid, without rounding the
+  /// winnings down.
+  pub fn net_result(&self, outcome: Outcome) -> f64 {
+    let payout = match outcome {
+      Outcome::Natural => self.natural_payout,
+      Outcome::Made21 => self.made_21_payout,
+      Outcome::Charlie => self.charlie_payout,
+      Outcome::Win => self.win_payout,
+      Outcome::Push => return 0.0,
+      Outcome::Surrender => return -0.5,
+      Outcome::Bust | Outcome::Loss => return -1.0,
+    };
+    payout.numerator as f64 / payout.denominator as f64
+  }
+
+  /// The redeal a freshly dealt two card hand is entitled to, if any.
+  pub fn redeal_trigger(&self, hand: &Hand) -> Option<&RedealTrigger> {
+    match hand.cards() {
+      [first, second] => self
+        .redeals
+        .iter()
+        .find(|trigger| trigger.matches(*first, *second, &self.worth)),
+      _ => None,
+    }
+  }
+
+  /// Checks the deck can't run out in a round of `players` players. At worst
+  /// every player splits into as many hands as allowed, and every hand,
+  /// the bank's too, keeps drawing the lowest cards until a last one busts
+  /// it.
+  pub fn check_deck(&self, players: usize) -> Result<(), String> {
+    let hands = players * self.split_hands + 1;
+    let mut worths = Vec::with_capacity(self.deck.size() as usize);
+    for value in Value::ALL {
+      let copies = self.deck.copies(value) * 4 * self.deck.decks;
+      worths.extend((0..copies).map(|_| self.worth.lowest(value)));
+    }
+    let jokers = self.deck.jokers * self.deck.decks;
+    worths.extend((0..jokers).map(|_| self.worth.lowest(Value::Joker)));
+    worths.sort_unstable();
+
+    let mut room = 21 * hands as u32;
+    let below_bust = worths
+      .iter()
+      .take_while(|worth| match room.checked_sub(**worth) {
+        Some(left) => {
+          room = left;
+          true
+        }
+        None => false,
+      })
+      .count();
+    let needed = below_bust + hands;
+    match worths.len() >= needed {
+      true => Ok(()),
+      false => Err(format!(
+        "The {} deck of {} cards can run out in a round, it needs {needed}",
+        self.deck.name,
+        worths.len()
+      )),
+    }
+  }
+
+  /// Money the bank has to set aside to cover the largest possible winnings
+  /// of a bid.
+  pub fn cover(&self, bid: u32) -> u32 {
+    Outcome::ALL
+      .iter()
+      .map(|outcome| self.winnings(*outcome, bid))
+      .max()
+      .unwrap()
+  }
+}
+
+impl fmt::Display for Outcome {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let outcome = match self {
+      Outcome::Natural => "natural 21",
+      Outcome::Made21 => "made 21",
+      Outcome::Charlie => "charlie",
+      Outcome::Win => "beat bank",
+      Outcome::Bust => "bust",
+      Outcome::Push => "push",
+      Outcome::Loss => "lost to bank",
+      Outcome::Surrender => "surrendered",
+    };
+    write!(f, "{}", outcome)
+  }
+}
+
+impl fmt::Display for WorthScheme {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let worths = Value::ALL
+      .iter()
+      .chain([Value::Joker].iter())
+      .map(|value| {
+        let worths = (0..32)
+          .filter(|worth| self.worths(*value) & 1 << worth != 0)
+          .map(|worth| worth.to_string())
+          .collect::<Vec<_>>()
+          .join("/");
+        format!("{}={}", value.to_string().trim(), worths)
+      })
+      .collect::<Vec<_>>()
+      .join(" ");
+    write!(f, "{} ({})", self.name, worths)
+  }
+}
+
+impl fmt::Display for DeckComposition {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.name)?;
+    if self.jokers > 0 {
+      write!(f, " with {} jokers", self.jokers)?;
+    }
+    if self.decks > 1 {
+      write!(f, ", {} decks", self.decks)?;
+    }
+    write!(f, " ({} cards)", self.size())
+  }
+}
+
+impl fmt::Display for RedealTrigger {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      RedealTrigger::Cards(a, b) => write!(f, "{}+{}", a.to_string().trim(), b.to_string().trim()),
+      RedealTrigger::Pair => write!(f, "pair"),
+      RedealTrigger::Total(total) => write!(f, "total-{}", total),
+    }
+  }
+}
+
+impl fmt::Display for Payout {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}:{}", self.numerator, self.denominator)
+  }
+}
+
+impl fmt::Display for Rules {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    writeln!(f, "Variant: {}", self.variant.name())?;
+    writeln!(f, "Deck: {}", self.deck)?;
+    writeln!(f, "Card worth: {}", self.worth)?;
+    writeln!(f, "Natural 21 pays {}", self.natural_payout)?;
+    writeln!(f, "Made 21 pays {}", self.made_21_payout)?;
+    match self.charlie_cards {
+      Some(cards) => writeln!(f, "{}-card charlie pays {}", cards, self.charlie_payout)?,
+      None => writeln!(f, "No charlie")?,
+    }
+    writeln!(f, "Beating the bank pays {}", self.win_payout)?;
+    let tie = match self.tie {
+      TieRule::Bank => "Ties go to the bank",
+      TieRule::Push => "Ties are a push",
+      TieRule::Player => "Ties go to the player",
+    };
+    writeln!(f, "{}", tie)?;
+    self.variant.fmt_rules(self, f)
+  }
+}
@@ -0,0 +1,326 @@
+//! `scenario` mode: replays one starting situation, like a player holding
+//! 10 and 6 against an open 7, with the unknown cards shuffled anew every
+//! round. It shows what each strategy does with the hand and how that pays
+//! off, and which first action is worth the most.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::rules::Rules;
+use crate::strategy::{Action, Actions, PlayStrategy, PlayerView};
+use crate::variant::{open_bank_card, SeatHand};
+use crate::{
+  check_play_strategy, exit_with, flag_values, parse_bank_strategy, parse_play_strategy,
+  parse_rules, parse_values, Game, Player, Value,
+};
+
+const DEFAULT_ROUNDS: u32 = 100_000;
+/// Shuffles every strategy sees unless `--seed` says otherwise
+const DEFAULT_SEED: u64 = 21;
+const DEFAULT_STRATEGIES: [&str; 3] = ["deck-aware", "fixed-6", "basic"];
+/// Enough for the bank to cover every bid without going broke
+pub const BANKROLL: u32 = 1_000_000_000;
+
+/// `scenario <player cards> <bank card>`, e.g. `scenario 10,6 7`, with the
+/// usual `--rule`, `--bank` and `--player` options, `--rounds <n>`,
+/// `--removed <cards>` for cards known to be out of the deck,
+/// `--then <strategy>` for how hands go on after a forced first action and
+/// `--seed <n>` for the shuffles. Every strategy and first action gets the
+/// same shuffles, so their differences are measured on the same rounds.
+pub fn run(args: &[String]) {
+  let usage = "Usage: scenario <player cards> <bank card>, like: scenario 10,6 7";
+  let (Some(hand), Some(bank_card)) = (args.get(2), args.get(3)) else {
+    exit_with(usage);
+  };
+  let hand = parse_values(hand).unwrap_or_else(|error| exit_with(&error));
+  let bank_card: Value = bank_card
+    .parse()
+    .unwrap_or_else(|error: String| exit_with(&error));
+  if hand.len() != 2 {
+    exit_with(usage);
+  }
+  let rounds = parse_rounds(args, DEFAULT_ROUNDS);
+  let seed = match flag_values(args, "--seed").first() {
+    Some(seed) => seed
+      .parse()
+      .unwrap_or_else(|_| exit_with(&format!("Invalid seed '{seed}'"))),
+    None => DEFAULT_SEED,
+  };
+  let removed = match flag_values(args, "--removed").first() {
+    Some(list) => parse_values(list).unwrap_or_else(|error| exit_with(&error)),
+    None => Vec::new(),
+  };
+  let mut strategies = flag_values(args, "--player");
+  if strategies.is_empty() {
+    strategies = DEFAULT_STRATEGIES.to_vec();
+  }
+  let then = flag_values(args, "--then")
+    .first()
+    .copied()
+    .unwrap_or("basic");
+  for name in strategies.iter().chain([&then]) {
+    check_play_strategy(name);
+  }
+
+  let rules = parse_rules(args);
+  rules
+    .check_deck(1)
+    .unwrap_or_else(|error| exit_with(&error));
+  let scenario = Scenario {
+    args,
+    rules,
+    // Every variant deals a lone player a card, then the bank, then the
+    // player their second card
+    stack: vec![hand[0], bank_card, hand[1]],
+    hand: hand.clone(),
+    removed,
+    rounds,
+    seed,
+  };
+  println!(
+    "Scenario: {} and {} against {}, {} rounds each",
+    hand[0].to_string().trim(),
+    hand[1].to_string().trim(),
+    bank_card.to_string().trim(),
+    rounds
+  );
+  println!("Variant: {}", scenario.rules.variant.name());
+  if !scenario.removed.is_empty() {
+    let removed: Vec<_> = scenario
+      .removed
+      .iter()
+      .map(|value| value.to_string().trim().to_string())
+      .collect();
+    println!("Removed from the deck: {}", removed.join(" "));
+  }
+  println!("EV per 1$ of starting bid, give or take one standard error");
+
+  println!("Strategies:");
+  for name in strategies {
+    let (decision, strategy) = scenario.decide(parse_play_strategy(name));
+    let decision = match decision {
+      Some((_, action)) => format!("{action:?}"),
+      None => "no decision".to_string(),
+    };
+    let estimate = scenario.play(strategy);
+    println!("  {:<14} {:<12} {}", name, decision, estimate);
+  }
+
+  let (decision, then_strategy) = scenario.decide(parse_play_strategy(then));
+  let Some((allowed, _)) = decision else {
+    println!("The deal settles the hand, there is nothing to decide");
+    return;
+  };
+  println!("First action, then playing on with {then}:");
+  // Every forced action plays on with the same strategy, so a bot is only
+  // started once
+  let then_strategy = Rc::new(RefCell::new(then_strategy));
+  let mut results = Vec::new();
+  for action in allowed.iter() {
+    let forced = Forced {
+      first: *action,
+      hand: scenario.hand.clone(),
+      then: then_strategy.clone(),
+    };
+    let estimate = scenario.play(Box::new(forced));
+    println!("  {:<27} {}", format!("{action:?}"), estimate);
+    results.push((*action, estimate.mean));
+  }
+  results.sort_by(|a, b| b.1.total_cmp(&a.1));
+  match results.as_slice() {
+    [(best, best_ev), (second, second_ev), ..] => println!(
+      "Best: {:?}, {:.4} per 1$ bid better than {:?}",
+      best,
+      best_ev - second_ev,
+      second
+    ),
+    [(only, _)] => println!("Best: {:?}, the only choice", only),
+    [] => {}
+  }
+}
+
+/// The number of rounds given with `--rounds`, or `default`. There has to
+/// be at least one to estimate anything.
+pub fn parse_rounds(args: &[String], default: u32) -> u32 {
+  match flag_values(args, "--rounds").first() {
+    Some(rounds) => rounds
+      .parse()
+      .ok()
+      .filter(|rounds| *rounds > 0)
+      .unwrap_or_else(|| exit_with(&format!("Invalid number of rounds '{rounds}'"))),
+    None => default,
+  }
+}
+
+struct Scenario<'a> {
+  args: &'a [String],
+  rules: Rules,
+  /// Cards dealt first so the player and bank start out as asked
+  stack: Vec<Value>,
+  hand: Vec<Value>,
+  removed: Vec<Value>,
+  rounds: u32,
+  seed: u64,
+}
+
+impl Scenario<'_> {
+  /// A game with one player using `strategy`, dealing the scenario every
+  /// round.
+  fn game(&self, strategy: Box<dyn PlayStrategy>) -> Game {
+    let mut bank = Player::new(0);
+    bank.money = BANKROLL;
+    let mut game = Game::new(
+      bank,
+      parse_bank_strategy(self.args),
+      self.rules.clone(),
+      StdRng::seed_from_u64(self.seed),
+    );
+    game.add_player(Player::new(1), strategy);
+    game
+      .remove(self.removed.clone())
+      .and_then(|_| game.stack(self.stack.clone()))
+      .unwrap_or_else(|error| exit_with(&error));
+    game
+  }
+
+  /// Deals the scenario once and asks `strategy` what it does with the
+  /// starting hand, as `Game::starting_actions` does, handing the strategy
+  /// back.
+  fn decide(
+    &self,
+    strategy: Box<dyn PlayStrategy>,
+  ) -> (Option<(Actions, Action)>, Box<dyn PlayStrategy>) {
+    let mut game = self.game(strategy);
+    let decision = game.starting_actions();
+    let id = game.players[0].id;
+    (decision, game.strategies.remove(&id).unwrap())
+  }
+
+  /// Plays the scenario over and over with `strategy`.
+  fn play(&self, strategy: Box<dyn PlayStrategy>) -> Estimate {
+    let mut game = self.game(strategy);
+    let results = (0..self.rounds).map(|_| game.play_alone());
+    Estimate::of(results)
+  }
+}
+
+impl Game {
+  /// Plays a round and returns how much the only player won or lost per 1$
+  /// of their starting bid, nothing if they didn't bid.
+  pub(super) fn play_alone(&mut self) -> f64 {
+    let money = self.players[0].money as f64;
+    self.play_round().unwrap_or_else(|error| exit_with(&error));
+    let result = self.players[0].money as f64 - money;
+    let bid = self.seats[0].first().map_or(0, |seat| seat.stake);
+    self.clean_up();
+    match bid {
+      0 => 0.0,
+      bid => result / bid as f64,
+    }
+  }
+}
+
+/// Average result of a number of rounds with its standard error.
+#[derive(Clone, Copy)]
+pub struct Estimate {
+  pub mean: f64,
+  pub error: f64,
+}
+
+impl Estimate {
+  pub fn of(results: impl Iterator<Item = f64>) -> Estimate {
+    let (mut count, mut sum, mut squares) = (0.0, 0.0, 0.0);
+    for result in results {
+      count += 1.0;
+      sum += result;
+      squares += result * result;
+    }
+    let mean = sum / count;
+    let variance = (squares / count - mean * mean).max(0.0);
+    Estimate {
+      mean,
+      error: (variance / count).sqrt(),
+    }
+  }
+}
+
+impl std::fmt::Display for Estimate {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "EV {:>+8.4} ± {:.4}", self.mean, self.error)
+  }
+}
+
+/// Takes `first` on the scenario's starting hand and plays on with `then`.
+/// Keeps the starting hand when it may choose to have it dealt again.
+struct Forced {
+  first: Action,
+  hand: Vec<Value>,
+  then: Rc<RefCell<Box<dyn PlayStrategy>>>,
+}
+
+impl PlayStrategy for Forced {
+  fn name(&self) -> String {
+    format!("{:?} then {}", self.first, self.then.borrow().name())
+  }
+
+  fn bid(&mut self, player: &Player, view: &PlayerView) -> u32 {
+    self.then.borrow_mut().bid(player, view)
+  }
+
+  fn hit(&mut self, view: &PlayerView) -> bool {
+    self.then.borrow_mut().hit(view)
+  }
+
+  fn action(&mut self, view: &PlayerView, allowed: &[Action]) -> Action {
+    let starting = view
+      .hand
+      .cards()
+      .iter()
+      .map(|card| card.value())
+      .eq(self.hand.iter().copied());
+    match starting && allowed.contains(&self.first) {
+      true => self.first,
+      false => self.then.borrow_mut().action(view, allowed),
+    }
+  }
+
+  fn redeal(&mut self, _view: &PlayerView) -> bool {
+    false
+  }
+
+  fn insurance(&mut self, view: &PlayerView) -> bool {
+    self.then.borrow_mut().insurance(view)
+  }
+}
+
+impl Game {
+  /// Deals a round and asks the first player what they would do with their
+  /// starting hand, without playing it. Returns the actions they could take
+  /// and the one they picked, or `None` if the deal already settled the hand
+  /// or the player didn't bid.
+  fn starting_actions(&mut self) -> Option<(Actions, Action)> {
+    let variant = self.rules.variant;
+    variant.deal(self).unwrap_or_else(|error| exit_with(&error));
+    let player = &self.players[0];
+    if player.bid == 0 {
+      return None;
+    }
+    let seat = [SeatHand::new(player.hand, player.bid)];
+    if variant.settle_early(&self.rules, &seat[0]).is_some() {
+      return None;
+    }
+    let allowed = variant.allowed_actions(&self.rules, &self.bank, player, &seat, 0);
+    let view = PlayerView {
+      hand: &player.hand,
+      bank_card: open_bank_card(&self.bank),
+      deck: &self.deck,
+      rules: &self.rules,
+      bank_stands_on: self.bank_strategy.stands_on(),
+    };
+    let strategy = self.strategies.get_mut(&player.id).unwrap();
+    Some((allowed, strategy.action(&view, &allowed)))
+  }
+}
@@ -0,0 +1,689 @@
+//! `server` mode: runs a game on a TCP port for people on the local network,
+//! who join with `client` or a program of their own. Every message is a
+//! line of words separated by spaces. A player starts with
+//!
+//! ```text
+//! JOIN <name>
+//! ```
+//!
+//! and gets `WELCOME <seat> <variant>` back, or `ERROR` and `BYE` when every
+//! seat is taken. Once the table is full the server plays round after round,
+//! sending:
+//!
+//! - `ROUND <number>` when a round starts
+//! - `HAND <cards> total <total> soft|hard`, `BANK <card>|?` and
+//!   `TABLE <cards>` before every question, bids included: the client's own
+//!   hand, just `HAND` before their first card, the bank's open card and
+//!   every open card on the table
+//! - `BID? <money>`, answered with `BID <amount>`
+//! - `ACTION? <allowed actions>`, answered with `ACTION <action>`, like
+//!   `ACTION? hit stand double` and `ACTION hit`
+//! - `REDEAL?` and `INSURANCE?`, answered with `REDEAL yes|no` and
+//!   `INSURANCE yes|no`
+//! - `ERROR <message>` when an answer can't be used, after which the
+//!   question comes again
+//! - `RESULT <money> <change>` when the round is over
+//! - `BYE` when the server stops
+//!
+//! Cards are written as their value: `A`, `2` to `10`, `J`, `Q`, `K` or
+//! `JK`. Answering `QUIT` to a question leaves the table, after which the
+//! seat sits out: it bids nothing, stands, takes redeals and turns down
+//! insurance. A question left unanswered for a minute gets the same answer,
+//! and the player is asked again next time. The server stops when everyone
+//! has left.
+//!
+//! Spectators can come and go at any time with `WATCH <name>`, get
+//! `WATCHING <variant>` back and are sent only what everyone at the table
+//! can see, as it happens:
+//!
+//! - `ROUND <number>` when a round starts
+//! - `OPEN bank <card>` and `OPEN <player> <hand> <card>` for every card
+//!   dealt face up, with hands numbered from 1 as they are split
+//! - `BID <player> <amount>`, `ACTION <player> <action>`,
+//!   `REDEAL <player> yes|no` and `INSURANCE <player> yes|no` for every
+//!   decision
+//!
+//! After the round every card is shown, with `REVEAL <player> <cards>` for
+//! every hand played, `REVEAL bank <cards>` and `RESULT <player> <money>
+//! <change>` for every player, followed by `END <number>`.
+
+use std::cell::RefCell;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::events::{Event, Observer, Seat};
+use crate::scenario::BANKROLL;
+use crate::strategy::{Action, PlayStrategy, PlayerView};
+use crate::{
+  exit_with, flag_values, parse_bank_strategy, parse_rules, Card, Game, Player, Visibility,
+};
+
+/// Listens on every network interface, so the whole local network can join
+const DEFAULT_ADDRESS: &str = "0.0.0.0:2121";
+const DEFAULT_SEATS: u32 = 2;
+/// How long a player gets to answer a question
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// `server` with the usual `--rule` and `--bank` options, `--address
+/// <host:port>` to listen on and `--seats <n>` players to wait for.
+pub fn run(args: &[String]) {
+  let address = flag_values(args, "--address")
+    .first()
+    .copied()
+    .unwrap_or(DEFAULT_ADDRESS);
+  let seats = match flag_values(args, "--seats").first() {
+    Some(seats) => seats
+      .parse()
+      .ok()
+      .filter(|seats| *seats > 0)
+      .unwrap_or_else(|| exit_with(&format!("Invalid number of seats '{seats}'"))),
+    None => DEFAULT_SEATS,
+  };
+  let rules = parse_rules(args);
+  rules
+    .check_deck(seats as usize)
+    .unwrap_or_else(|error| exit_with(&error));
+  let listener = TcpListener::bind(address)
+    .unwrap_or_else(|error| exit_with(&format!("Could not listen on {address}: {error}")));
+
+  // Every client is greeted on a thread of their own, so one that doesn't
+  // say who they are keeps no one else waiting
+  let (sender, arrivals) = mpsc::channel();
+  thread::spawn(move || {
+    for stream in listener.incoming().flatten() {
+      let sender = sender.clone();
+      thread::spawn(move || {
+        if let Some(arrival) = Connection::greet(stream) {
+          let _ = sender.send(arrival);
+        }
+      });
+    }
+  });
+
+  let variant = rules.variant.name();
+  let audience = Rc::new(RefCell::new(Audience::new(arrivals, variant)));
+  let mut bank = Player::new(0);
+  bank.money = BANKROLL;
+  let mut game = Game::new(
+    bank,
+    parse_bank_strategy(args),
+    rules,
+    StdRng::from_entropy(),
+  );
+  game.add_observer(Box::new(Spectating(audience.clone())));
+  println!("{variant}, waiting for {seats} players on {address}");
+  let mut connections = Vec::new();
+  while connections.len() < seats as usize {
+    let Some(mut connection) = audience.borrow_mut().next_player() else {
+      exit_with("Stopped accepting players");
+    };
+    let id = connections.len() as u32 + 1;
+    connection.time_out(TIMEOUT);
+    connection.send(&format!("WELCOME {id} {variant}"));
+    println!("{} joined at seat {id}", connection.name);
+    audience.borrow_mut().seated.push(connection.name.clone());
+    let connection = Rc::new(RefCell::new(connection));
+    let seat = RemoteSeat {
+      connection: connection.clone(),
+      audience: audience.clone(),
+    };
+    game.add_player(Player::new(id), Box::new(seat));
+    connections.push(connection);
+  }
+
+  let mut round = 0;
+  while connections
+    .iter()
+    .any(|connection| connection.borrow().connected)
+  {
+    round += 1;
+    for connection in &connections {
+      connection.borrow_mut().send(&format!("ROUND {round}"));
+    }
+    audience.borrow_mut().start(round);
+    let before: Vec<(u32, u32)> = game.players.iter().map(|p| (p.id, p.money)).collect();
+    game.play_round().unwrap_or_else(|error| exit_with(&error));
+
+    let mut audience = audience.borrow_mut();
+    let name = |id: u32| connections[id as usize - 1].borrow().name.clone();
+    for (player, hands) in game.players.iter().zip(&game.seats) {
+      for seat in hands {
+        audience.broadcast(&format!(
+          "REVEAL {} {}",
+          name(player.id),
+          cards(seat.hand.cards())
+        ));
+      }
+    }
+    audience.broadcast(&format!("REVEAL bank {}", cards(game.bank.hand.cards())));
+    for (id, money) in &before {
+      let player = game.players.iter().find(|player| player.id == *id).unwrap();
+      let change = player.money as i64 - *money as i64;
+      let result = format!("{} {change:+}", player.money);
+      connections[*id as usize - 1]
+        .borrow_mut()
+        .send(&format!("RESULT {result}"));
+      audience.broadcast(&format!("RESULT {} {result}", name(*id)));
+    }
+    audience.broadcast(&format!("END {round}"));
+    drop(audience);
+
+    // Players keep their money from round to round
+    let after: Vec<(u32, u32)> = game.players.iter().map(|p| (p.id, p.money)).collect();
+    game.clean_up();
+    for player in &mut game.players {
+      player.money = after.iter().find(|(id, _)| *id == player.id).unwrap().1;
+    }
+  }
+  println!("Everyone left after {round} rounds");
+}
+
+/// What a client comes to do.
+#[derive(PartialEq, Debug)]
+enum Greeting {
+  Join,
+  Watch,
+}
+
+/// A client of the server.
+struct Connection {
+  name: String,
+  reader: BufReader<TcpStream>,
+  writer: TcpStream,
+  connected: bool,
+  /// How long the client gets to answer, once they are seated
+  timeout: Option<Duration>,
+  /// Questions the client answered too late
+  timeouts: u32,
+}
+
+impl Connection {
+  /// Waits for the client to send `JOIN <name>` or `WATCH <name>`.
+  fn greet(stream: TcpStream) -> Option<(Greeting, Connection)> {
+    let mut connection = Connection {
+      name: String::new(),
+      reader: BufReader::new(stream.try_clone().ok()?),
+      writer: stream,
+      connected: true,
+      timeout: None,
+      timeouts: 0,
+    };
+    loop {
+      let line = connection.receive()?;
+      let (greeting, name) = match line.split_once(' ') {
+        Some(("JOIN", name)) => (Greeting::Join, name.trim()),
+        Some(("WATCH", name)) => (Greeting::Watch, name.trim()),
+        _ => (Greeting::Join, ""),
+      };
+      if name.is_empty() {
+        connection.send("ERROR Expected JOIN <name> or WATCH <name>");
+        continue;
+      }
+      // Names are single words in the messages about them
+      connection.name = name.replace(char::is_whitespace, "_");
+      return Some((greeting, connection));
+    }
+  }
+
+  fn send(&mut self, line: &str) {
+    if self.connected && writeln!(self.writer, "{line}").is_err() {
+      self.connected = false;
+    }
+  }
+
+  /// Gives up waiting for the client's answers after `timeout`.
+  fn time_out(&mut self, timeout: Duration) {
+    self.timeout = Some(timeout);
+    if self.writer.set_read_timeout(self.timeout).is_err() {
+      self.connected = false;
+    }
+  }
+
+  /// The next line from the client, `None` once they are gone or when they
+  /// take too long to send it.
+  fn receive(&mut self) -> Option<String> {
+    let mut line = String::new();
+    match self.reader.read_line(&mut line) {
+      Err(error) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+        self.timeouts += 1;
+        if self.timeouts == 1 {
+          let timeout = self.timeout.unwrap_or_default();
+          eprintln!("{} took longer than {timeout:?} to answer", self.name);
+        }
+        None
+      }
+      Ok(0) | Err(_) => {
+        self.connected = false;
+        None
+      }
+      Ok(_) => Some(line.trim_end().to_string()),
+    }
+  }
+  /// Asks `question` until the client answers `<reply> <answer>` with an
+  /// answer `parse` accepts, or leaves.
+  fn ask<T>(
+    &mut self,
+    question: &str,
+    reply: &str,
+    parse: impl Fn(&str) -> Option<T>,
+  ) -> Option<T> {
+    while self.connected {
+      self.send(question);
+      let line = self.receive()?;
+      if line == "QUIT" {
+        self.send("BYE");
+        self.connected = false;
+        return None;
+      }
+      let answer = line
+        .strip_prefix(reply)
+        .and_then(|answer| answer.strip_prefix(' '))
+        .and_then(|answer| parse(answer.trim()));
+      match answer {
+        Some(answer) => return Some(answer),
+        None => self.send(&format!(
+          "ERROR Expected {reply} and an answer to {question}"
+        )),
+      }
+    }
+    None
+  }
+
+  /// Tells the client what they can see before a decision.
+  fn send_view(&mut self, view: &PlayerView) {
+    let hand = view.hand;
+    let kind = if hand.is_soft() { "soft" } else { "hard" };
+    match hand.cards() {
+      [] => self.send("HAND"),
+      dealt => self.send(&format!(
+        "HAND {} total {} {kind}",
+        cards(dealt),
+        hand.greatest_value()
+      )),
+    }
+    let bank = view
+      .bank_card
+      .map_or("?".to_string(), |card| cards(&[*card]));
+    self.send(&format!("BANK {bank}"));
+    self.send(&format!("TABLE {}", cards(&view.deck.open)));
+  }
+}
+
+impl Drop for Connection {
+  fn drop(&mut self) {
+    self.send("BYE");
+    if self.timeouts > 0 {
+      eprintln!("{}: {} late answers", self.name, self.timeouts);
+    }
+  }
+}
+
+/// Card values separated by spaces.
+pub fn cards(cards: &[Card]) -> String {
+  let values: Vec<String> = cards
+    .iter()
+    .map(|card| card.value().to_string().trim().to_string())
+    .collect();
+  values.join(" ")
+}
+
+fn yes_or_no(answer: &str) -> Option<bool> {
+  match answer {
+    "yes" => Some(true),
+    "no" => Some(false),
+    _ => None,
+  }
+}
+
+/// Spectators, and clients still arriving.
+struct Audience {
+  spectators: Vec<Connection>,
+  /// Names of the players, by seat from seat 1
+  seated: Vec<String>,
+  arrivals: Receiver<(Greeting, Connection)>,
+  variant: &'static str,
+}
+
+impl Audience {
+  fn new(arrivals: Receiver<(Greeting, Connection)>, variant: &'static str) -> Audience {
+    Audience {
+      spectators: Vec::new(),
+      seated: Vec::new(),
+      arrivals,
+      variant,
+    }
+  }
+
+  /// Waits for the next player, letting spectators in meanwhile.
+  fn next_player(&mut self) -> Option<Connection> {
+    loop {
+      match self.arrivals.recv().ok()? {
+        (Greeting::Join, connection) => return Some(connection),
+        (Greeting::Watch, connection) => self.watch(connection),
+      }
+    }
+  }
+
+  fn watch(&mut self, mut connection: Connection) {
+    connection.send(&format!("WATCHING {}", self.variant));
+    println!("{} is watching", connection.name);
+    self.spectators.push(connection);
+  }
+
+  /// Lets in the spectators who arrived since, and turns players away now
+  /// that every seat is taken.
+  fn admit(&mut self) {
+    while let Ok((greeting, mut connection)) = self.arrivals.try_recv() {
+      match greeting {
+        Greeting::Watch => self.watch(connection),
+        Greeting::Join => connection.send("ERROR Every seat is taken"),
+      }
+    }
+  }
+
+  fn broadcast(&mut self, line: &str) {
+    self.admit();
+    for spectator in &mut self.spectators {
+      spectator.send(line);
+    }
+    self.spectators.retain(|spectator| spectator.connected);
+  }
+
+  fn start(&mut self, round: u32) {
+    self.broadcast(&format!("ROUND {round}"));
+  }
+}
+
+/// Shows the spectators every card dealt face up as it is dealt.
+struct Spectating(Rc<RefCell<Audience>>);
+
+impl Observer for Spectating {
+  fn observe(&mut self, event: &Event) {
+    if let Event::CardDealt {
+      to,
+      card,
+      visibility: Visibility::All,
+    } = *event
+    {
+      let mut audience = self.0.borrow_mut();
+      let to = match to {
+        Seat::Bank => "bank".to_string(),
+        Seat::Player { id, hand } => format!("{} {}", audience.seated[id as usize - 1], hand + 1),
+      };
+      audience.broadcast(&format!("OPEN {to} {}", cards(&[card])));
+    }
+  }
+}
+
+/// Plays a seat by asking the client at it, telling the spectators what
+/// they decide. When they take too long or have left it bids nothing and
+/// stands.
+struct RemoteSeat {
+  connection: Rc<RefCell<Connection>>,
+  audience: Rc<RefCell<Audience>>,
+}
+
+impl RemoteSeat {
+  /// Shows the player their view of the table and asks.
+  fn ask<T>(
+    &mut self,
+    view: &PlayerView,
+    question: &str,
+    reply: &str,
+    parse: impl Fn(&str) -> Option<T>,
+  ) -> Option<T> {
+    let mut connection = self.connection.borrow_mut();
+    connection.send_view(view);
+    connection.ask(question, reply, parse)
+  }
+
+  fn announce(&self, decision: &str, answer: &str) {
+    let line = format!("{decision} {} {answer}", self.connection.borrow().name);
+    self.audience.borrow_mut().broadcast(&line);
+  }
+}
+
+impl PlayStrategy for RemoteSeat {
+  fn name(&self) -> String {
+    self.connection.borrow().name.clone()
+  }
+
+  fn bid(&mut self, player: &Player, view: &PlayerView) -> u32 {
+    let question = format!("BID? {}", player.money);
+    let bid = self
+      .ask(view, &question, "BID", |answer| {
+        answer.parse().ok().filter(|bid| *bid <= player.money)
+      })
+      .unwrap_or(0);
+    self.announce("BID", &bid.to_string());
+    bid
+  }
+
+  fn hit(&mut self, view: &PlayerView) -> bool {
+    self.action(view, &[Action::Hit, Action::Stand]) == Action::Hit
+  }
+
+  fn action(&mut self, view: &PlayerView, allowed: &[Action]) -> Action {
+    let names: Vec<&str> = allowed.iter().map(|action| action.name()).collect();
+    let question = format!("ACTION? {}", names.join(" "));
+    let action = self
+      .ask(view, &question, "ACTION", |answer| {
+        allowed
+          .iter()
+          .find(|action| action.name() == answer)
+          .copied()
+      })
+      .unwrap_or(Action::Stand);
+    self.announce("ACTION", action.name());
+    action
+  }
+
+  fn redeal(&mut self, view: &PlayerView) -> bool {
+    let redeal = self
+      .ask(view, "REDEAL?", "REDEAL", yes_or_no)
+      .unwrap_or(true);
+    self.announce("REDEAL", if redeal { "yes" } else { "no" });
+    redeal
+  }
+
+  fn insurance(&mut self, view: &PlayerView) -> bool {
+    let insurance = self
+      .ask(view, "INSURANCE?", "INSURANCE", yes_or_no)
+      .unwrap_or(false);
+    self.announce("INSURANCE", if insurance { "yes" } else { "no" });
+    insurance
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::strategy::with_view;
+  use crate::{Suit, Value};
+  use std::sync::mpsc::Sender;
+
+  /// A connection to a client that sends `lines`, with everything the
+  /// client got once the connection is dropped
+  fn connect(lines: &str) -> (Connection, thread::JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let lines = lines.to_string();
+    let client = thread::spawn(move || {
+      let mut stream = TcpStream::connect(address).unwrap();
+      write!(stream, "{lines}").unwrap();
+      stream.shutdown(std::net::Shutdown::Write).unwrap();
+      BufReader::new(stream).lines().map(Result::unwrap).collect()
+    });
+    let (stream, _) = listener.accept().unwrap();
+    let (_, connection) = Connection::greet(stream).unwrap();
+    (connection, client)
+  }
+
+  fn audience() -> (Rc<RefCell<Audience>>, Sender<(Greeting, Connection)>) {
+    let (sender, arrivals) = mpsc::channel();
+    let audience = Audience::new(arrivals, "Test");
+    (Rc::new(RefCell::new(audience)), sender)
+  }
+
+  /// A seat whose client joins and sends `answers`
+  fn seat(
+    answers: &str,
+    audience: &Rc<RefCell<Audience>>,
+  ) -> (RemoteSeat, thread::JoinHandle<Vec<String>>) {
+    let (mut connection, client) = connect(&format!("JOIN tester\n{answers}"));
+    connection.send("WELCOME 1 Test");
+    audience.borrow_mut().seated.push(connection.name.clone());
+    let seat = RemoteSeat {
+      connection: Rc::new(RefCell::new(connection)),
+      audience: audience.clone(),
+    };
+    (seat, client)
+  }
+
+  /// Asks `seat` for a bid on an open 7 against an open 10
+  fn bid(seat: &mut RemoteSeat) -> u32 {
+    let player = Player::new(1);
+    with_view(&[Value::Seven], Some(Value::Ten), |view| {
+      seat.bid(&player, view)
+    })
+  }
+
+  /// Asks `seat` to play 10 and A against a 7
+  fn decide(seat: &mut RemoteSeat, allowed: &[Action]) -> Action {
+    let cards = [Value::Ten, Value::Ace];
+    with_view(&cards, Some(Value::Seven), |view| {
+      seat.action(view, allowed)
+    })
+  }
+
+  #[test]
+  fn asks_again_until_the_answer_fits() {
+    let (audience, _) = audience();
+    let (mut seat, client) = seat("BID 500\nBID 20\nACTION double\nACTION stand\n", &audience);
+    assert_eq!(bid(&mut seat), 20);
+    assert_eq!(
+      decide(&mut seat, &[Action::Hit, Action::Stand]),
+      Action::Stand
+    );
+    drop(seat);
+    let received = client.join().unwrap();
+    assert_eq!(
+      received,
+      [
+        "WELCOME 1 Test",
+        "HAND 7 total 7 hard",
+        "BANK 10",
+        "TABLE 10",
+        "BID? 100",
+        "ERROR Expected BID and an answer to BID? 100",
+        "BID? 100",
+        "HAND 10 A total 21 soft",
+        "BANK 7",
+        "TABLE 7",
+        "ACTION? hit stand",
+        "ERROR Expected ACTION and an answer to ACTION? hit stand",
+        "ACTION? hit stand",
+        "BYE",
+      ]
+    );
+  }
+
+  #[test]
+  fn a_client_that_quits_sits_out() {
+    let (audience, _) = audience();
+    let (mut seat, client) = seat("QUIT\n", &audience);
+    assert_eq!(bid(&mut seat), 0);
+    assert_eq!(
+      decide(&mut seat, &[Action::Hit, Action::Stand]),
+      Action::Stand
+    );
+    assert!(!seat.connection.borrow().connected);
+    drop(seat);
+    assert_eq!(
+      client.join().unwrap(),
+      [
+        "WELCOME 1 Test",
+        "HAND 7 total 7 hard",
+        "BANK 10",
+        "TABLE 10",
+        "BID? 100",
+        "BYE"
+      ]
+    );
+  }
+
+  #[test]
+  fn a_client_that_takes_too_long_sits_the_question_out() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let client = thread::spawn(move || {
+      let mut stream = TcpStream::connect(address).unwrap();
+      writeln!(stream, "JOIN slow").unwrap();
+      thread::sleep(Duration::from_millis(300));
+      writeln!(stream, "BID 20").unwrap();
+      stream.shutdown(std::net::Shutdown::Write).unwrap();
+      BufReader::new(stream).lines().count()
+    });
+    let (stream, _) = listener.accept().unwrap();
+    let (_, mut connection) = Connection::greet(stream).unwrap();
+    connection.time_out(Duration::from_millis(100));
+    let (audience, _) = audience();
+    let mut seat = RemoteSeat {
+      connection: Rc::new(RefCell::new(connection)),
+      audience,
+    };
+    assert_eq!(bid(&mut seat), 0);
+    assert_eq!(seat.connection.borrow().timeouts, 1);
+    assert!(seat.connection.borrow().connected);
+    thread::sleep(Duration::from_millis(300));
+    assert_eq!(bid(&mut seat), 20);
+    drop(seat);
+    client.join().unwrap();
+  }
+
+  #[test]
+  fn spectators_see_only_open_cards_and_decisions() {
+    let (audience, arrivals) = audience();
+    let (spectator, watching) = connect("WATCH fan\n");
+    audience.borrow_mut().watch(spectator);
+    let (mut seat, _) = seat("BID 20\nACTION stand\n", &audience);
+    bid(&mut seat);
+    let mut spectating = Spectating(audience.clone());
+    let mut bank_card = Card::new(Suit::Hearts, Value::Seven);
+    bank_card.visibility = Visibility::All;
+    spectating.observe(&Event::dealt(Seat::Bank, bank_card));
+    let mut closed = Card::new(Suit::Spades, Value::King);
+    closed.visibility = Visibility::Owner;
+    spectating.observe(&Event::dealt(Seat::player(1), closed));
+    let mut split = Card::new(Suit::Clubs, Value::Four);
+    split.visibility = Visibility::All;
+    spectating.observe(&Event::dealt(Seat::Player { id: 1, hand: 1 }, split));
+    // A player arriving at a full table is turned away
+    let (late, turned_away) = connect("JOIN late\n");
+    arrivals.send((Greeting::Join, late)).unwrap();
+    decide(&mut seat, &[Action::Hit, Action::Stand]);
+    drop((seat, spectating, audience));
+    assert_eq!(
+      watching.join().unwrap(),
+      [
+        "WATCHING Test",
+        "BID tester 20",
+        "OPEN bank 7",
+        "OPEN tester 2 4",
+        "ACTION tester stand",
+        "BYE"
+      ]
+    );
+    assert_eq!(
+      turned_away.join().unwrap(),
+      ["ERROR Every seat is taken", "BYE"]
+    );
+  }
+}
@@ -0,0 +1,442 @@
+//! Works out when to hit and when to stand for the house rules by dynamic
+//! programming over every hand the player can draw to, instead of guessing a
+//! threshold. The bank is assumed to stand on a fixed total, and the
+//! player's cards are drawn from a full deck. `solve` mode prints the
+//! table it comes to.
+
+use std::collections::HashMap;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::bank_odds::{parse_stand_on, BankDistribution, BankOdds};
+use crate::rules::{Outcome, Rules};
+use crate::strategy::{Action, BasicStrategy, PlayStrategy, PlayerView};
+use crate::variant::SeatHand;
+use crate::{parse_rules, Card, Deck, Hand, Suit, Value};
+
+/// The bank strategy the solver plays against by default, standing on 17
+pub const STAND_ON: u32 = 17;
+
+/// EV of standing and hitting on a hand against each bank card.
+pub struct Solver {
+  rules: Rules,
+  stand_on: u32,
+  /// Cards the player has not seen per value, from Ace to Joker
+  counts: [u32; 14],
+  /// Whether `counts` leaves out the bank's open card already
+  open_card_seen: bool,
+  /// Chance of drawing each value, from Ace to Joker
+  draws: [f64; 14],
+  /// How the bank ends for each open card it can show, and for a bank whose
+  /// cards are all closed at index 14, worked out when first needed
+  banks: [Option<BankDistribution>; 15],
+  /// EV of standing and of hitting, per hand and bank card
+  memo: HashMap<(u32, u32, usize, usize), (f64, f64)>,
+}
+
+impl Solver {
+  /// Solves for a full deck, as for a player who only goes by their total.
+  pub fn new(rules: Rules, stand_on: u32) -> Solver {
+    let counts = Deck::new(&rules.deck, &rules.worth, StdRng::seed_from_u64(0)).counts();
+    Solver::with_counts(rules, stand_on, counts, false)
+  }
+
+  /// Solves for the cards a player has not seen yet, the bank's open card
+  /// and their own hand left out. Further cards are taken to come from these
+  /// as they are at the decision.
+  pub fn unseen(rules: Rules, stand_on: u32, counts: [u32; 14]) -> Solver {
+    Solver::with_counts(rules, stand_on, counts, true)
+  }
+
+  fn with_counts(rules: Rules, stand_on: u32, counts: [u32; 14], open_card_seen: bool) -> Solver {
+    let cards: u32 = counts.iter().sum();
+    let mut draws = [0.0; 14];
+    for (draw, count) in draws.iter_mut().zip(counts) {
+      *draw = count as f64 / cards.max(1) as f64;
+    }
+    Solver {
+      rules,
+      stand_on,
+      counts,
+      open_card_seen,
+      draws,
+      banks: [None; 15],
+      memo: HashMap::new(),
+    }
+  }
+
+  /// How the bank's hand ends, from the open card at `bank` on.
+  fn bank(&mut self, bank: usize) -> BankDistribution {
+    if let Some(distribution) = self.banks[bank] {
+      return distribution;
+    }
+    let mut odds = BankOdds::new(&self.rules, self.stand_on);
+    let mut hand = Hand::new();
+    let mut left = self.counts;
+    if bank < 14 {
+      let value = Value::from_u32(bank as u32 + 1);
+      hand.add_card(Card::new(Suit::Spades, value), &self.rules.worth);
+      if !self.open_card_seen {
+        left[bank] = left[bank].saturating_sub(1);
+      }
+    }
+    let mut distribution = odds.distribution(&hand, &left);
+    // A bank that peeked has no natural once the players get to play
+    if bank < 14 && self.rules.peek && distribution.natural > 0.0 && distribution.natural < 1.0 {
+      let rest = 1.0 - distribution.natural;
+      distribution.natural = 0.0;
+      distribution
+        .totals
+        .iter_mut()
+        .for_each(|chance| *chance /= rest);
+      distribution.bust /= rest;
+    }
+    self.banks[bank] = Some(distribution);
+    distribution
+  }
+
+  /// EV per 1$ bid of standing and of hitting once and playing on as well as
+  /// possible, on `hand` against a bank showing `bank_card`.
+  pub fn evs(&mut self, hand: &Hand, bank_card: Option<Value>) -> (f64, f64) {
+    let bank = bank_card.map_or(14, |value| value.to_u32() as usize - 1);
+    self.hand_evs(hand, bank)
+  }
+
+  /// Whether hitting `hand` against `bank_card` is worth more than standing,
+  /// or standing is not allowed.
+  pub fn hits(&mut self, hand: &Hand, bank_card: Option<Value>) -> bool {
+    let (stand, hit) = self.evs(hand, bank_card);
+    hit > stand
+  }
+
+  fn hand_evs(&mut self, hand: &Hand, bank: usize) -> (f64, f64) {
+    // Hands with the same totals and number of cards play out the same
+    let key = (hand.totals, hand.lowest, hand.cards().len(), bank);
+    if let Some(evs) = self.memo.get(&key) {
+      return *evs;
+    }
+    let stand = match hand.greatest_value() >= self.rules.min_stand {
+      true => self.stand_ev(hand, bank),
+      false => f64::NEG_INFINITY,
+    };
+    let mut hit = 0.0;
+    for (index, chance) in self.draws.into_iter().enumerate() {
+      if chance == 0.0 {
+        continue;
+      }
+      let mut next = *hand;
+      let value = Value::from_u32(index as u32 + 1);
+      next.add_card(Card::new(Suit::Spades, value), &self.rules.worth);
+      hit += chance * self.best_ev(&next, bank);
+    }
+    self.memo.insert(key, (stand, hit));
+    (stand, hit)
+  }
+
+  /// Every hand one more card makes of `hands`, leaving out those that
+  /// settle right away, with each set of totals once.
+  fn draw_all(&self, hands: &[Hand]) -> Vec<Hand> {
+    let mut next: Vec<Hand> = Vec::new();
+    for hand in hands {
+      for (index, chance) in self.draws.iter().enumerate() {
+        let mut drawn = *hand;
+        let value = Value::from_u32(index as u32 + 1);
+        drawn.add_card(Card::new(Suit::Spades, value), &self.rules.worth);
+        let seen = next
+          .iter()
+          .any(|other| (other.totals, other.lowest) == (drawn.totals, drawn.lowest));
+        if *chance > 0.0 && !seen && !self.settles(&drawn) {
+          next.push(drawn);
+        }
+      }
+    }
+    next
+  }
+
+  /// Whether the hand is over before the player gets to decide on it.
+  fn settles(&self, hand: &Hand) -> bool {
+    let seat = SeatHand::new(*hand, 1);
+    self
+      .rules
+      .variant
+      .settle_early(&self.rules, &seat)
+      .is_some()
+  }
+
+  /// EV of a hand the player is about to decide on, or that settles now.
+  fn best_ev(&mut self, hand: &Hand, bank: usize) -> f64 {
+    let seat = SeatHand::new(*hand, 1);
+    if let Some(outcome) = self.rules.variant.settle_early(&self.rules, &seat) {
+      return self.rules.net_result(outcome);
+    }
+    let (stand, hit) = self.hand_evs(hand, bank);
+    stand.max(hit)
+  }
+
+  /// EV of standing, against every way the bank's hand can end.
+  fn stand_ev(&mut self, hand: &Hand, bank: usize) -> f64 {
+    let distribution = self.bank(bank);
+    let rules = &self.rules;
+    let total = hand.greatest_value();
+    let (win, tie) = match hand.is_natural() {
+      true => (Outcome::Natural, Outcome::Natural),
+      false => (Outcome::Win, rules.tie.outcome()),
+    };
+    let against_natural = match hand.is_natural() {
+      true => rules.tie.outcome(),
+      false => Outcome::Loss,
+    };
+    let mut ev = distribution.natural * rules.net_result(against_natural);
+    ev += distribution.bust * rules.net_result(win);
+    for (bank_total, chance) in distribution.totals.iter().enumerate() {
+      let outcome = match total.cmp(&(bank_total as u32)) {
+        std::cmp::Ordering::Greater => win,
+        std::cmp::Ordering::Equal => tie,
+        std::cmp::Ordering::Less => Outcome::Loss,
+      };
+      ev += chance * rules.net_result(outcome);
+    }
+    ev
+  }
+}
+
+/// Prints whether to hit (H) or stand (S) on every hand the player can hold,
+/// by number of cards, against each bank card. `?` is a bank without an open
+/// card.
+pub fn run(args: &[String]) {
+  let rules = parse_rules(args);
+  let stand_on = parse_stand_on(args);
+  let counts = Deck::new(&rules.deck, &rules.worth, StdRng::seed_from_u64(0)).counts();
+  let bank_cards: Vec<Option<Value>> = (0..14)
+    .filter(|index| counts[*index] > 0)
+    .map(|index| Some(Value::from_u32(index as u32 + 1)))
+    .chain([None])
+    .collect();
+  let mut solver = Solver::new(rules.clone(), stand_on);
+
+  println!(
+    "Hit (H) or stand (S), against a bank standing on {stand_on} ({})",
+    rules.variant.name()
+  );
+  // Every hand the player can hold, one card count at a time
+  let mut hands = vec![Hand::new()];
+  for _ in 0..2 {
+    hands = solver.draw_all(&hands);
+  }
+  while !hands.is_empty() {
+    let len = hands[0].cards().len();
+    let mut rows: Vec<(bool, u32, &Hand)> = Vec::new();
+    for hand in &hands {
+      let row = (hand.is_soft(), hand.greatest_value(), hand);
+      if !rows
+        .iter()
+        .any(|(soft, total, _)| (*soft, *total) == (row.0, row.1))
+      {
+        rows.push(row);
+      }
+    }
+    rows.sort_by_key(|(soft, total, _)| (*soft, *total));
+
+    print!("\n{len} cards  ");
+    for bank_card in &bank_cards {
+      match bank_card {
+        Some(value) => print!(" {value}"),
+        None => print!("  ?"),
+      }
+    }
+    println!();
+    for (soft, total, hand) in rows {
+      print!("{} {total:>2}", if soft { "soft" } else { "hard" });
+      for bank_card in &bank_cards {
+        print!(
+          "  {}",
+          if solver.hits(hand, *bank_card) {
+            "H"
+          } else {
+            "S"
+          }
+        );
+      }
+      println!();
+    }
+    hands = solver.draw_all(&hands);
+  }
+}
+
+/// Hits or stands as the solver works out for the rules of the game and the
+/// bank's threshold, which it does the first time it is asked. Doubling,
+/// splitting and the other actions are left to the basic chart.
+#[derive(Default)]
+pub struct Optimal {
+  solver: Option<Solver>,
+}
+
+impl PlayStrategy for Optimal {
+  fn name(&self) -> String {
+    "optimal".to_string()
+  }
+
+  fn hit(&mut self, view: &PlayerView) -> bool {
+    let solver = self
+      .solver
+      .get_or_insert_with(|| Solver::new(view.rules.clone(), solved_stand_on(view, &mut false)));
+    solver.hits(view.hand, view.bank_card.map(|card| card.value()))
+  }
+
+  fn action(&mut self, view: &PlayerView, allowed: &[Action]) -> Action {
+    basic_or_hit(self, view, allowed)
+  }
+}
+
+/// What the basic chart does with the hand, unless that is hitting or
+/// standing, which is left to `strategy`.
+fn basic_or_hit(strategy: &mut dyn PlayStrategy, view: &PlayerView, allowed: &[Action]) -> Action {
+  match BasicStrategy::chart(view, allowed) {
+    Action::Hit | Action::Stand if strategy.hit(view) => Action::Hit,
+    Action::Hit | Action::Stand => Action::Stand,
+    action => action,
+  }
+}
+
+/// The total the bank stands on in `view`. A bank that doesn't play to a
+/// fixed total is solved as standing on `STAND_ON`, with a warning unless
+/// `warned` already.
+fn solved_stand_on(view: &PlayerView, warned: &mut bool) -> u32 {
+  view.bank_stands_on.unwrap_or_else(|| {
+    if !*warned {
+      eprintln!("The bank doesn't stand on a fixed total, solving as if it stands on {STAND_ON}");
+      *warned = true;
+    }
+    STAND_ON
+  })
+}
+
+/// Most solved card situations `CompositionDependent` keeps
+const CACHED_SOLVERS: usize = 10_000;
+
+/// Works out the best play anew from the cards it has seen this round: its
+/// own hand, the open cards of the others and the bank's open card. Solved
+/// situations are kept for when the same cards come up again.
+#[derive(Default)]
+pub struct CompositionDependent {
+  solvers: HashMap<[u32; 14], Solver>,
+  /// Told that the bank doesn't stand on a fixed total
+  warned: bool,
+}
+
+impl PlayStrategy for CompositionDependent {
+  fn name(&self) -> String {
+    "composition".to_string()
+  }
+
+  fn hit(&mut self, view: &PlayerView) -> bool {
+    let counts = view.deck.unseen(view.hand);
+    if self.solvers.len() >= CACHED_SOLVERS && !self.solvers.contains_key(&counts) {
+      self.solvers.clear();
+    }
+    let stand_on = solved_stand_on(view, &mut self.warned);
+    let solver = self
+      .solvers
+      .entry(counts)
+      .or_insert_with(|| Solver::unseen(view.rules.clone(), stand_on, counts));
+    solver.hits(view.hand, view.bank_card.map(|card| card.value()))
+  }
+
+  fn action(&mut self, view: &PlayerView, allowed: &[Action]) -> Action {
+    basic_or_hit(self, view, allowed)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn hand(values: &[Value], rules: &Rules) -> Hand {
+    let mut hand = Hand::new();
+    for value in values {
+      hand.add_card(Card::new(Suit::Spades, *value), &rules.worth);
+    }
+    hand
+  }
+
+  #[test]
+  fn casino_decisions_match_the_basic_chart() {
+    let mut rules = Rules::default();
+    rules.set("variant", "casino").unwrap();
+    let mut solver = Solver::new(rules.clone(), STAND_ON);
+    use Value::*;
+    let cases = [
+      (&[Ten, Two][..], Two, true),
+      (&[Ten, Two][..], Four, false),
+      (&[Ten, Three][..], Two, false),
+      (&[Ten, Six][..], Seven, true),
+      (&[Ten, Seven][..], Ace, false),
+      (&[Ace, Seven][..], Nine, true),
+      (&[Ace, Seven][..], Eight, false),
+      (&[Ace, Six][..], Six, true),
+    ];
+    for (values, bank, hits) in cases {
+      let hand = hand(values, &rules);
+      assert_eq!(
+        solver.hits(&hand, Some(bank)),
+        hits,
+        "{hand} against {bank}"
+      );
+    }
+  }
+
+  #[test]
+  fn ten_and_two_hit_against_four_from_a_single_deck() {
+    let mut rules = Rules::default();
+    rules.set("variant", "casino").unwrap();
+    let mut counts = [4; 14];
+    counts[13] = 0;
+    for value in [Value::Ten, Value::Two, Value::Four] {
+      counts[value.to_u32() as usize - 1] -= 1;
+    }
+    let ten_two = hand(&[Value::Ten, Value::Two], &rules);
+    let mut solver = Solver::unseen(rules.clone(), STAND_ON, counts);
+    assert!(solver.hits(&ten_two, Some(Value::Four)));
+    // Going by the total alone, 12 stands against a 4
+    let mut solver = Solver::new(rules.clone(), STAND_ON);
+    assert!(!solver.hits(&ten_two, Some(Value::Four)));
+  }
+
+  #[test]
+  fn hitting_hard_21_always_loses() {
+    let mut rules = Rules::default();
+    rules.set("variant", "casino").unwrap();
+    let mut solver = Solver::new(rules.clone(), STAND_ON);
+    let (stand, hit) = solver.evs(
+      &hand(&[Value::Ten, Value::Five, Value::Six], &rules),
+      Some(Value::Ten),
+    );
+    assert!((hit + 1.0).abs() < 1e-9, "{hit}");
+    assert!(stand > 0.5, "{stand}");
+  }
+
+  #[test]
+  fn a_seventh_card_that_does_not_bust_is_a_dutch_charlie() {
+    let rules = Rules::default();
+    let mut solver = Solver::new(rules.clone(), STAND_ON);
+    use Value::*;
+    let six_cards = hand(&[Two, Two, Two, Two, Three, Three], &rules);
+    let (_, hit) = solver.evs(&six_cards, Some(Ten));
+    // Ace to Seven makes a charlie, Eight and up bust
+    let charlie = 28.0 / 52.0 * rules.net_result(Outcome::Charlie);
+    let expected = charlie - 24.0 / 52.0;
+    assert!((hit - expected).abs() < 1e-9, "{hit}");
+  }
+
+  #[test]
+  fn dutch_ties_to_the_bank_make_17_hit_against_an_ace() {
+    let mut rules = Rules::default();
+    let seventeen = hand(&[Value::Ten, Value::Seven], &rules);
+    let mut solver = Solver::new(rules.clone(), STAND_ON);
+    assert!(solver.hits(&seventeen, Some(Value::Ace)));
+    rules.set("tie", "push").unwrap();
+    let mut solver = Solver::new(rules.clone(), STAND_ON);
+    assert!(!solver.hits(&seventeen, Some(Value::Ace)));
+  }
+}
@@ -0,0 +1,725 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use crate::rules::{Outcome, Rules, TieRule, WorthScheme};
+use crate::solver::{CompositionDependent, Optimal};
+use crate::variant::SeatHand;
+use crate::{Card, Deck, Hand, Player, Visibility};
+
+/// A decision a player can make about their hand.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Action {
+  Hit,
+  Stand,
+  /// Double the bid and draw exactly one more card
+  Double,
+  /// Split a pair into two hands with a bid each
+  Split,
+  /// Give up the hand for half the bid back
+  Surrender,
+  /// Raise the bid by the original stake for another closed card
+  Buy,
+}
+
+impl Action {
+  pub const ALL: [Action; 6] = [
+    Action::Hit,
+    Action::Stand,
+    Action::Double,
+    Action::Split,
+    Action::Surrender,
+    Action::Buy,
+  ];
+
+  /// The lower case name rules and bots refer to the action by.
+  pub fn name(self) -> &'static str {
+    match self {
+      Action::Hit => "hit",
+      Action::Stand => "stand",
+      Action::Double => "double",
+      Action::Split => "split",
+      Action::Surrender => "surrender",
+      Action::Buy => "buy",
+    }
+  }
+}
+
+/// The actions allowed on a hand, kept inline so that asking for them doesn't
+/// allocate.
+#[derive(Clone, Copy)]
+pub struct Actions {
+  actions: [Action; 6],
+  len: usize,
+}
+
+impl Actions {
+  pub fn new() -> Actions {
+    Actions {
+      actions: [Action::Stand; 6],
+      len: 0,
+    }
+  }
+
+  pub fn push(&mut self, action: Action) {
+    self.actions[self.len] = action;
+    self.len += 1;
+  }
+}
+
+impl Deref for Actions {
+  type Target = [Action];
+
+  fn deref(&self) -> &[Action] {
+    &self.actions[..self.len]
+  }
+}
+
+impl<const N: usize> From<[Action; N]> for Actions {
+  fn from(actions: [Action; N]) -> Actions {
+    let mut allowed = Actions::new();
+    actions.into_iter().for_each(|action| allowed.push(action));
+    allowed
+  }
+}
+
+/// What a player sees when deciding on their hand.
+pub struct PlayerView<'a> {
+  pub hand: &'a Hand,
+  /// The bank's open card, if it has one
+  pub bank_card: Option<&'a Card>,
+  pub deck: &'a Deck,
+  pub rules: &'a Rules,
+  /// Total the bank stands on, if it plays to a fixed one
+  pub bank_stands_on: Option<u32>,
+}
+
+/// Decides how much a player bids and how they play their hand.
+pub trait PlayStrategy {
+  /// Short name used to label the strategy in the statistics.
+  fn name(&self) -> String;
+
+  /// How much `player` bids, seeing the cards dealt before the bids.
+  fn bid(&mut self, _player: &Player, _view: &PlayerView) -> u32 {
+    1
+  }
+
+  /// Returns `true` if the player wants another card.
+  fn hit(&mut self, view: &PlayerView) -> bool;
+
+  /// Picks one of the `allowed` actions. Anything else counts as standing,
+  /// or as hitting where standing is not allowed. Strategies that only hit or
+  /// stand can rely on `hit`.
+  fn action(&mut self, view: &PlayerView, _allowed: &[Action]) -> Action {
+    match self.hit(view) {
+      true => Action::Hit,
+      false => Action::Stand,
+    }
+  }
+
+  /// Returns `true` if the player takes a redeal they are entitled to. Only
+  /// asked when the rules leave the choice to the player.
+  fn redeal(&mut self, _view: &PlayerView) -> bool {
+    true
+  }
+
+  /// Returns `true` if the player insures against a bank natural.
+  fn insurance(&mut self, _view: &PlayerView) -> bool {
+    false
+  }
+}
+
+/// Looks up a player strategy by the name used on the command line:
+/// `deck-aware`, `fixed-<n>`, `basic`, `optimal` or `composition`.
+pub fn play_strategy_by_name(name: &str) -> Option<Box<dyn PlayStrategy>> {
+  match name {
+    "deck-aware" => Some(Box::new(DeckAware)),
+    "basic" => Some(Box::new(BasicStrategy)),
+    "optimal" => Some(Box::new(Optimal::default())),
+    "composition" => Some(Box::new(CompositionDependent::default())),
+    _ => {
+      let expected_value = name.strip_prefix("fixed-")?.parse().ok()?;
+      Some(Box::new(FixedThreshold::new(expected_value)))
+    }
+  }
+}
+
+/// Keeps drawing as long as the expected worth of the remaining deck fits.
+pub struct DeckAware;
+
+impl PlayStrategy for DeckAware {
+  fn name(&self) -> String {
+    "deck-aware".to_string()
+  }
+
+  fn hit(&mut self, view: &PlayerView) -> bool {
+    view.hand.greatest_value() as f64 + view.deck.expected_value() <= 21.0
+  }
+
+  /// Only starts over with a hand it would want to draw on anyway.
+  fn redeal(&mut self, view: &PlayerView) -> bool {
+    self.hit(view)
+  }
+}
+
+/// Keeps drawing as long as the smallest value plus a fixed expected card
+/// worth fits.
+pub struct FixedThreshold {
+  pub expected_value: f64,
+}
+
+impl FixedThreshold {
+  pub fn new(expected_value: f64) -> FixedThreshold {
+    FixedThreshold { expected_value }
+  }
+}
+
+impl PlayStrategy for FixedThreshold {
+  fn name(&self) -> String {
+    format!("fixed({})", self.expected_value)
+  }
+
+  fn hit(&mut self, view: &PlayerView) -> bool {
+    view.hand.smallest_value() as f64 + self.expected_value <= 21.0
+  }
+}
+
+/// The textbook casino basic strategy for multiple decks with doubling after a
+/// split allowed.
+pub struct BasicStrategy;
+
+impl BasicStrategy {
+  pub fn chart(view: &PlayerView, allowed: &[Action]) -> Action {
+    let can = |action| allowed.contains(&action);
+    let worth = &view.rules.worth;
+    let bank = view
+      .bank_card
+      .map(|card| worth.worths(card.value()) & ((1 << 12) - 1))
+      .filter(|worths| *worths != 0)
+      .map_or(10, |worths| 31 - worths.leading_zeros());
+    let total = view.hand.greatest_value();
+    let soft = view.hand.is_soft();
+
+    if can(Action::Split) {
+      let split = match worth.lowest(view.hand.cards()[0].value()) {
+        1 | 8 => true,
+        9 => !matches!(bank, 7 | 10 | 11),
+        2 | 3 | 7 => bank <= 7,
+        6 => bank <= 6,
+        4 => (5..=6).contains(&bank),
+        _ => false,
+      };
+      if split {
+        return Action::Split;
+      }
+    }
+    if can(Action::Surrender)
+      && !soft
+      && ((total == 16 && bank >= 9) || (total == 15 && bank == 10))
+    {
+      return Action::Surrender;
+    }
+    let double = match (soft, total) {
+      (true, 13..=14) => (5..=6).contains(&bank),
+      (true, 15..=16) => (4..=6).contains(&bank),
+      (true, 17..=18) => (3..=6).contains(&bank),
+      (false, 9) => (3..=6).contains(&bank),
+      (false, 10) => bank <= 9,
+      (false, 11) => bank <= 10 || view.rules.bank_hits_soft_17,
+      _ => false,
+    };
+    if double && can(Action::Double) {
+      return Action::Double;
+    }
+    let stand = match soft {
+      true => total >= 19 || (total == 18 && bank <= 8),
+      false => {
+        total >= 17 || (total >= 13 && bank <= 6) || (total == 12 && (4..=6).contains(&bank))
+      }
+    };
+    match stand {
+      true => Action::Stand,
+      false => Action::Hit,
+    }
+  }
+}
+
+impl PlayStrategy for BasicStrategy {
+  fn name(&self) -> String {
+    "basic".to_string()
+  }
+
+  fn hit(&mut self, view: &PlayerView) -> bool {
+    BasicStrategy::chart(view, &[Action::Hit, Action::Stand]) == Action::Hit
+  }
+
+  fn action(&mut self, view: &PlayerView, allowed: &[Action]) -> Action {
+    BasicStrategy::chart(view, allowed)
+  }
+}
+
+/// What the bank sees when deciding whether to draw. The bank acts last, so
+/// it knows its own cards and the open cards of every player.
+pub struct BankView<'a> {
+  pub hand: &'a Hand,
+  seats: &'a [Vec<SeatHand>],
+  pub deck: &'a Deck,
+  pub rules: &'a Rules,
+}
+
+impl<'a> BankView<'a> {
+  pub fn new(
+    hand: &'a Hand,
+    seats: &'a [Vec<SeatHand>],
+    deck: &'a Deck,
+    rules: &'a Rules,
+  ) -> BankView<'a> {
+    BankView {
+      hand,
+      seats,
+      deck,
+      rules,
+    }
+  }
+
+  /// The bids of the players still in, assuming nobody stood below
+  /// `players_stand_on`.
+  fn stakes(&self, players_stand_on: u32) -> impl Iterator<Item = Stake> + '_ {
+    self
+      .seats
+      .iter()
+      .flatten()
+      .filter(|seat| !seat.settled)
+      .map(move |seat| {
+        let mut open_cards = Hand::new();
+        let mut closed = 0;
+        for card in seat.hand.cards() {
+          match card.visibility {
+            Visibility::All => open_cards.add_card(*card, &self.rules.worth),
+            _ => closed += 1,
+          }
+        }
+        let totals = match closed {
+          0 => vec![(seat.hand.greatest_value(), 1.0)],
+          _ => self.closed_card_totals(open_cards, closed, players_stand_on),
+        };
+        Stake {
+          bid: seat.bid as f64,
+          winnings: self.rules.winnings(Outcome::Win, seat.bid) as f64,
+          tie: self.rules.tie,
+          totals,
+        }
+      })
+  }
+
+  /// Possible totals of a player who is still in with `closed` cards the
+  /// bank can't see besides `open_cards`, weighted by how likely each closed
+  /// card is according to the remaining deck. Totals below
+  /// `players_stand_on` are left out, as the player would not have stood on
+  /// them, and so are hands the variant would already have paid out.
+  fn closed_card_totals(
+    &self,
+    open_cards: Hand,
+    closed: usize,
+    players_stand_on: u32,
+  ) -> Vec<(u32, f64)> {
+    let counts = self.deck.value_counts();
+    let cards: u32 = counts.iter().map(|(_, count)| count).sum();
+    // Hands with the same totals play the same, whatever cards made them
+    let mut hands = vec![(open_cards, 1.0)];
+    for _ in 0..closed {
+      let mut drawn: Vec<(Hand, f64)> = Vec::new();
+      for (hand, chance) in &hands {
+        for (card, count) in &counts {
+          let mut next = *hand;
+          next.add_card(*card, &self.rules.worth);
+          let chance = chance * *count as f64 / cards as f64;
+          let same = drawn
+            .iter_mut()
+            .find(|(other, _)| other.totals == next.totals && other.lowest == next.lowest);
+          match same {
+            Some((_, other)) => *other += chance,
+            None => drawn.push((next, chance)),
+          }
+        }
+      }
+      hands = drawn;
+    }
+
+    let variant = self.rules.variant;
+    let mut totals = Vec::new();
+    let mut weight = 0.0;
+    for (hand, chance) in hands {
+      let total = hand.greatest_value();
+      let seat = SeatHand::new(hand, 0);
+      if total < players_stand_on || variant.settle_early(self.rules, &seat).is_some() {
+        continue;
+      }
+      totals.push((total, chance));
+      weight += chance;
+    }
+    totals
+      .into_iter()
+      .map(|(total, chance)| (total, chance / weight))
+      .collect()
+  }
+}
+
+/// A bid the bank still has to resolve, as far as the bank can tell.
+struct Stake {
+  bid: f64,
+  /// What the bank pays if the player beats it
+  winnings: f64,
+  tie: TieRule,
+  /// Chance of each final total the player can have given their open cards
+  totals: Vec<(u32, f64)>,
+}
+
+impl Stake {
+  /// Chance that the bank takes the bid when it stands on `bank_value`.
+  fn win_chance(&self, bank_value: u32) -> f64 {
+    if self.totals.is_empty() {
+      return 0.5;
+    }
+    self
+      .totals
+      .iter()
+      .filter(|(total, _)| {
+        *total < bank_value || (*total == bank_value && self.tie == TieRule::Bank)
+      })
+      .map(|(_, chance)| chance)
+      .sum()
+  }
+
+  /// Expected profit of the bank on this bid when it stands on `bank_value`.
+  fn profit(&self, bank_value: u32) -> f64 {
+    self
+      .totals
+      .iter()
+      .map(|(total, chance)| {
+        let profit = if *total < bank_value {
+          self.bid
+        } else if *total > bank_value {
+          -self.winnings
+        } else {
+          match self.tie {
+            TieRule::Bank => self.bid,
+            TieRule::Push => 0.0,
+            TieRule::Player => -self.winnings,
+          }
+        };
+        chance * profit
+      })
+      .sum()
+  }
+}
+
+/// Decides whether the bank draws another card.
+pub trait BankStrategy {
+  /// Short name used to label the strategy in the statistics.
+  fn name(&self) -> String;
+
+  /// Returns `true` if the bank wants another card.
+  fn hit(&mut self, view: &BankView) -> bool;
+
+  /// Total the bank stands on, if it plays to a fixed one.
+  fn stands_on(&self) -> Option<u32> {
+    None
+  }
+}
+
+/// Looks up a bank strategy by the name used on the command line:
+/// `stand-on-<n>`, `beat-majority` or `max-expected-profit`.
+pub fn bank_strategy_by_name(name: &str) -> Option<Box<dyn BankStrategy>> {
+  match name {
+    "beat-majority" => Some(Box::new(BeatMajority::new(15))),
+    "max-expected-profit" => Some(Box::new(MaxExpectedProfit::new(15))),
+    _ => {
+      let threshold = name.strip_prefix("stand-on-")?.parse().ok()?;
+      Some(Box::new(StandOn::new(threshold)))
+    }
+  }
+}
+
+/// Draws until the greatest value reaches a fixed threshold, like a casino
+/// dealer standing on 17.
+pub struct StandOn {
+  pub threshold: u32,
+}
+
+impl StandOn {
+  pub fn new(threshold: u32) -> StandOn {
+    StandOn { threshold }
+  }
+}
+
+impl BankStrategy for StandOn {
+  fn name(&self) -> String {
+    format!("stand-on({})", self.threshold)
+  }
+
+  fn hit(&mut self, view: &BankView) -> bool {
+    view.hand.greatest_value() < self.threshold
+  }
+
+  fn stands_on(&self) -> Option<u32> {
+    Some(self.threshold)
+  }
+}
+
+/// Draws until the bank is more likely than not to beat the players holding
+/// the majority of the stakes still on the table.
+pub struct BeatMajority {
+  /// Lowest total the bank assumes a player would stand on
+  pub players_stand_on: u32,
+}
+
+impl BeatMajority {
+  pub fn new(players_stand_on: u32) -> BeatMajority {
+    BeatMajority { players_stand_on }
+  }
+}
+
+impl BankStrategy for BeatMajority {
+  fn name(&self) -> String {
+    "beat-majority".to_string()
+  }
+
+  fn hit(&mut self, view: &BankView) -> bool {
+    let bank_value = view.hand.greatest_value();
+    if bank_value >= 21 {
+      return false;
+    }
+    let stakes: Vec<Stake> = view.stakes(self.players_stand_on).collect();
+    let total_stakes: f64 = stakes.iter().map(|stake| stake.bid).sum();
+    let beaten_stakes: f64 = stakes
+      .iter()
+      .filter(|stake| stake.win_chance(bank_value) > 0.5)
+      .map(|stake| stake.bid)
+      .sum();
+    total_stakes > 0.0 && beaten_stakes * 2.0 <= total_stakes
+  }
+}
+
+/// Draws when the expected profit of drawing (and then continuing to play
+/// optimally) is higher than the expected profit of standing. The next cards
+/// are assumed to be drawn from the current deck composition with
+/// replacement, which keeps the search small.
+pub struct MaxExpectedProfit {
+  /// Lowest total the bank assumes a player would stand on
+  pub players_stand_on: u32,
+  /// Kept between decisions so they don't have to be allocated again
+  stakes: Vec<Stake>,
+  draws: Vec<(Card, f64)>,
+  memo: HashMap<(u32, u32), f64>,
+}
+
+impl MaxExpectedProfit {
+  pub fn new(players_stand_on: u32) -> MaxExpectedProfit {
+    MaxExpectedProfit {
+      players_stand_on,
+      stakes: Vec::new(),
+      draws: Vec::new(),
+      memo: HashMap::new(),
+    }
+  }
+}
+
+impl BankStrategy for MaxExpectedProfit {
+  fn name(&self) -> String {
+    "max-expected-profit".to_string()
+  }
+
+  fn hit(&mut self, view: &BankView) -> bool {
+    self.stakes.clear();
+    self.stakes.extend(view.stakes(self.players_stand_on));
+    if self.stakes.is_empty() {
+      return false;
+    }
+    let cards_left = view.deck.cards.len() as f64;
+    self.draws.clear();
+    self.draws.extend(
+      view
+        .deck
+        .value_counts()
+        .into_iter()
+        .map(|(card, count)| (card, count as f64 / cards_left)),
+    );
+
+    self.memo.clear();
+    let stand = stand_profit(view.hand.greatest_value(), &self.stakes);
+    let hit = hit_profit(
+      view.hand,
+      &self.draws,
+      &view.rules.worth,
+      &self.stakes,
+      &mut self.memo,
+    );
+    hit > stand
+  }
+}
+
+/// Expected profit of the bank when it stands on `bank_value`.
+fn stand_profit(bank_value: u32, stakes: &[Stake]) -> f64 {
+  stakes.iter().map(|stake| stake.profit(bank_value)).sum()
+}
+
+/// Expected profit of the bank when it draws one card and then plays on
+/// optimally. Hands are memoised on their totals, which is all that matters
+/// for the rest of the bank's play.
+fn hit_profit(
+  hand: &Hand,
+  draws: &[(Card, f64)],
+  worth: &WorthScheme,
+  stakes: &[Stake],
+  memo: &mut HashMap<(u32, u32), f64>,
+) -> f64 {
+  let mut profit = 0.0;
+  for (card, chance) in draws {
+    let mut next = *hand;
+    next.add_card(*card, worth);
+    profit += chance * best_profit(&next, draws, worth, stakes, memo);
+  }
+  profit
+}
+
+fn best_profit(
+  hand: &Hand,
+  draws: &[(Card, f64)],
+  worth: &WorthScheme,
+  stakes: &[Stake],
+  memo: &mut HashMap<(u32, u32), f64>,
+) -> f64 {
+  if hand.is_bust() {
+    return -(stakes.iter().map(|stake| stake.winnings).sum::<f64>());
+  }
+  let key = (hand.totals, hand.lowest);
+  if let Some(profit) = memo.get(&key) {
+    return *profit;
+  }
+  let stand = stand_profit(hand.greatest_value(), stakes);
+  let profit = if hand.greatest_value() >= 21 {
+    stand
+  } else {
+    stand.max(hit_profit(hand, draws, worth, stakes, memo))
+  };
+  memo.insert(key, profit);
+  profit
+}
+
+/// Shows `decide` a hand of `cards` against an open `bank` card, or a bank
+/// without one, under the casino rules with the rest of the deck unseen.
+#[cfg(test)]
+pub fn with_view<T>(
+  cards: &[crate::Value],
+  bank: Option<crate::Value>,
+  decide: impl FnOnce(&PlayerView) -> T,
+) -> T {
+  use rand::SeedableRng;
+  let mut rules = Rules::default();
+  rules.set("variant", "casino").unwrap();
+  let mut hand = Hand::new();
+  for value in cards {
+    hand.add_card(Card::new(crate::Suit::Spades, *value), &rules.worth);
+  }
+  let mut deck = Deck::new(
+    &rules.deck,
+    &rules.worth,
+    rand::rngs::StdRng::seed_from_u64(0),
+  );
+  let bank_card = bank.map(|value| {
+    let mut card = Card::new(crate::Suit::Hearts, value);
+    card.open();
+    deck.open.push(card);
+    card
+  });
+  let view = PlayerView {
+    hand: &hand,
+    bank_card: bank_card.as_ref(),
+    deck: &deck,
+    rules: &rules,
+    bank_stands_on: Some(17),
+  };
+  decide(&view)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{Suit, Value};
+  use rand::rngs::StdRng;
+  use rand::SeedableRng;
+
+  /// The rules of `variant` with a full deck to draw from.
+  fn table(variant: &str) -> (Rules, Deck) {
+    let mut rules = Rules::default();
+    rules.set("variant", variant).unwrap();
+    let deck = Deck::new(&rules.deck, &rules.worth, StdRng::seed_from_u64(21));
+    (rules, deck)
+  }
+
+  /// A seat still in with a hand of `cards`, each dealt as visible as given.
+  fn seat(cards: &[(Value, Visibility)], rules: &Rules) -> Vec<SeatHand> {
+    let mut hand = Hand::new();
+    for (value, visibility) in cards {
+      let mut card = Card::new(Suit::Spades, *value);
+      card.visibility = *visibility;
+      hand.add_card(card, &rules.worth);
+    }
+    vec![SeatHand::new(hand, 10)]
+  }
+
+  #[test]
+  fn casino_players_have_their_known_total() {
+    use Value::*;
+    let (rules, deck) = table("casino");
+    let open = Visibility::All;
+    let seats = [
+      seat(&[(Ten, open), (Five, open), (Six, open)], &rules),
+      seat(&[(Ten, open), (Nine, open)], &rules),
+    ];
+    let bank = Hand::new();
+    let stakes = BankView::new(&bank, &seats, &deck, &rules)
+      .stakes(15)
+      .collect::<Vec<_>>();
+
+    assert_eq!(stakes[0].totals, [(21, 1.0)]);
+    assert_eq!(stakes[1].totals, [(19, 1.0)]);
+  }
+
+  #[test]
+  fn pontoon_players_can_have_any_total_of_both_closed_cards() {
+    use Value::*;
+    let (rules, deck) = table("pontoon");
+    let closed = Visibility::Owner;
+    let seats = [seat(&[(Ten, closed), (Nine, closed)], &rules)];
+    let bank = Hand::new();
+    let stakes = BankView::new(&bank, &seats, &deck, &rules)
+      .stakes(15)
+      .collect::<Vec<_>>();
+
+    let totals = &stakes[0].totals;
+    assert!(totals.iter().all(|(total, _)| (15..=21).contains(total)));
+    assert!(totals.iter().any(|(total, _)| *total == 21));
+    let chance: f64 = totals.iter().map(|(_, chance)| chance).sum();
+    assert!((chance - 1.0).abs() < 1e-9);
+    assert!(stakes[0].win_chance(21) > 0.5);
+  }
+
+  #[test]
+  fn dutch_players_on_21_were_paid_out_already() {
+    use Value::*;
+    let (rules, deck) = table("dutch");
+    let seats = [seat(
+      &[(Ten, Visibility::All), (Nine, Visibility::Owner)],
+      &rules,
+    )];
+    let bank = Hand::new();
+    let stakes = BankView::new(&bank, &seats, &deck, &rules)
+      .stakes(15)
+      .collect::<Vec<_>>();
+
+    let totals = &stakes[0].totals;
+    assert_eq!(totals.len(), 6);
+    assert!(totals.iter().all(|(total, _)| (15..21).contains(total)));
+  }
+}
@@ -0,0 +1,322 @@
+//! Play strategies written down as a decision table in a text file, so they
+//! can be tried without changing any code. A table looks like this:
+//!
+//! ```text
+//! # Anything after a # is a comment
+//! bank     A  2  3  4  5  6  7  8  9 10
+//! hard 11  H  D  D  D  D  D  D  D  D  D
+//! hard 16  H  S  S  S  S  S  H  H  H  H
+//! hard 16 3+ S S S  S  S  S  H  H  H  S
+//! soft 18  H  S  S  S  S  S  S  S  H  H
+//! ```
+//!
+//! The `bank` line lists the bank's open cards, with `?` for a bank that
+//! shows none. Cards that count the same as a listed one share its column.
+//! Every other line is a hand: `hard` or `soft`, its total, optionally the
+//! number of cards (`2`, or `3+` for three or more) and an action per bank
+//! card: H(it), S(tand), D(ouble), P (split), R (surrender) or B(uy). The row
+//! with the most exact number of cards applies. Double, split, surrender and
+//! buy hit when they are not allowed, and hands without a row or column hit
+//! below 17 and stand otherwise.
+
+use crate::strategy::{Action, PlayStrategy, PlayerView};
+use crate::Value;
+
+/// A play strategy read from a decision table.
+pub struct DecisionTable {
+  name: String,
+  /// Bank cards heading the columns, `None` for a bank without an open card
+  columns: Vec<Option<Value>>,
+  rows: Vec<Row>,
+}
+
+struct Row {
+  soft: bool,
+  total: u32,
+  cards: Cards,
+  actions: Vec<Action>,
+  /// Where the row is in the file, for pointing out duplicates
+  line: usize,
+}
+
+/// The number of cards a row is for.
+#[derive(PartialEq, Clone, Copy)]
+enum Cards {
+  Any,
+  AtLeast(usize),
+  Exactly(usize),
+}
+
+impl Cards {
+  fn matches(self, cards: usize) -> bool {
+    match self {
+      Cards::Any => true,
+      Cards::AtLeast(least) => cards >= least,
+      Cards::Exactly(exactly) => cards == exactly,
+    }
+  }
+
+  /// How closely the row pins down the number of cards, higher is closer.
+  fn precision(self) -> usize {
+    match self {
+      Cards::Any => 0,
+      Cards::AtLeast(least) => least,
+      Cards::Exactly(_) => usize::MAX,
+    }
+  }
+}
+
+impl DecisionTable {
+  /// Reads the table in the file at `path`. The strategy is named after it.
+  pub fn load(path: &str) -> Result<DecisionTable, String> {
+    let text = std::fs::read_to_string(path)
+      .map_err(|error| format!("Could not read strategy table '{path}': {error}"))?;
+    DecisionTable::parse(&format!("table:{path}"), &text).map_err(|error| format!("{path}:{error}"))
+  }
+
+  /// Parses a table, with errors starting with the line and column they
+  /// were found at, like `3:9: Unknown action 'X'`.
+  pub fn parse(name: &str, text: &str) -> Result<DecisionTable, String> {
+    let mut table = DecisionTable {
+      name: name.to_string(),
+      columns: Vec::new(),
+      rows: Vec::new(),
+    };
+    let mut header = false;
+    for (index, line) in text.lines().enumerate() {
+      let line_number = index + 1;
+      let line = line.split('#').next().unwrap_or_default();
+      // Every word with the column it starts at
+      let words: Vec<(usize, &str)> = line
+        .split_whitespace()
+        .map(|word| (word.as_ptr() as usize - line.as_ptr() as usize + 1, word))
+        .collect();
+      let error = |column: usize, message: String| format!("{line_number}:{column}: {message}");
+      let Some(&(column, first)) = words.first() else {
+        continue;
+      };
+
+      if !header {
+        if first != "bank" {
+          return Err(error(
+            column,
+            "Expected the bank line first, like: bank A 2 3".to_string(),
+          ));
+        }
+        for &(column, word) in &words[1..] {
+          let card = match word {
+            "?" => None,
+            _ => Some(word.parse().map_err(|message| error(column, message))?),
+          };
+          if table.columns.contains(&card) {
+            return Err(error(column, format!("Bank card '{word}' is listed twice")));
+          }
+          table.columns.push(card);
+        }
+        if table.columns.is_empty() {
+          return Err(error(
+            column,
+            "Expected the bank cards after 'bank'".to_string(),
+          ));
+        }
+        header = true;
+        continue;
+      }
+
+      let soft = match first {
+        "hard" => false,
+        "soft" => true,
+        _ => {
+          return Err(error(
+            column,
+            format!("Expected hard or soft, found '{first}'"),
+          ));
+        }
+      };
+      let mut words = words[1..].iter().peekable();
+      let end = line.trim_end().len() + 1;
+      let Some(&(column, word)) = words.next() else {
+        return Err(error(end, "Expected a total".to_string()));
+      };
+      let total = word
+        .parse()
+        .map_err(|_| error(column, format!("Expected a total, found '{word}'")))?;
+
+      let mut cards = Cards::Any;
+      if let Some(&&(column, word)) = words.peek() {
+        if word.starts_with(|c: char| c.is_ascii_digit()) {
+          words.next();
+          let invalid = || {
+            error(
+              column,
+              format!("Expected a number of cards like 2 or 3+, found '{word}'"),
+            )
+          };
+          cards = match word.strip_suffix('+') {
+            Some(least) => Cards::AtLeast(least.parse().map_err(|_| invalid())?),
+            None => Cards::Exactly(word.parse().map_err(|_| invalid())?),
+          };
+        }
+      }
+
+      let mut actions = Vec::new();
+      for &(column, word) in words {
+        if actions.len() == table.columns.len() {
+          let message = format!(
+            "Expected {} actions, one per bank card",
+            table.columns.len()
+          );
+          return Err(error(column, message));
+        }
+        actions.push(match word {
+          "H" => Action::Hit,
+          "S" => Action::Stand,
+          "D" => Action::Double,
+          "P" => Action::Split,
+          "R" => Action::Surrender,
+          "B" => Action::Buy,
+          _ => {
+            let message = format!("Unknown action '{word}', expected H, S, D, P, R or B");
+            return Err(error(column, message));
+          }
+        });
+      }
+      if actions.len() < table.columns.len() {
+        let message = format!(
+          "Expected {} actions, one per bank card, found {}",
+          table.columns.len(),
+          actions.len()
+        );
+        return Err(error(end, message));
+      }
+
+      let same = table
+        .rows
+        .iter()
+        .find(|row| (row.soft, row.total, row.cards) == (soft, total, cards));
+      if let Some(row) = same {
+        let message = format!("This hand already has a row at line {}", row.line);
+        return Err(error(1 + line.len() - line.trim_start().len(), message));
+      }
+      table.rows.push(Row {
+        soft,
+        total,
+        cards,
+        actions,
+        line: line_number,
+      });
+    }
+    if !header {
+      return Err("1:1: The table is empty".to_string());
+    }
+    Ok(table)
+  }
+
+  /// The action in the table for the hand in `view`, if it has one.
+  fn lookup(&self, view: &PlayerView) -> Option<Action> {
+    let hand = view.hand;
+    let (soft, total, cards) = (hand.is_soft(), hand.greatest_value(), hand.cards().len());
+    let worths = |value: Value| view.rules.worth.worths(value);
+    let column = self
+      .columns
+      .iter()
+      .position(|column| match (column, view.bank_card) {
+        (Some(value), Some(card)) => worths(*value) == worths(card.value()),
+        (None, None) => true,
+        _ => false,
+      })?;
+    let row = self
+      .rows
+      .iter()
+      .filter(|row| (row.soft, row.total) == (soft, total) && row.cards.matches(cards))
+      .max_by_key(|row| row.cards.precision())?;
+    Some(row.actions[column])
+  }
+}
+
+impl PlayStrategy for DecisionTable {
+  fn name(&self) -> String {
+    self.name.clone()
+  }
+
+  fn hit(&mut self, view: &PlayerView) -> bool {
+    self.action(view, &[Action::Hit, Action::Stand]) == Action::Hit
+  }
+
+  fn action(&mut self, view: &PlayerView, allowed: &[Action]) -> Action {
+    match self.lookup(view) {
+      Some(action) if allowed.contains(&action) => action,
+      Some(Action::Stand) => Action::Stand,
+      Some(_) => Action::Hit,
+      None if view.hand.greatest_value() < 17 => Action::Hit,
+      None => Action::Stand,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::strategy::with_view;
+
+  const TABLE: &str = "\
+# A small table
+bank     A  2  3  4  5  6  7  8  9 10
+hard 11  H  D  D  D  D  D  D  D  D  D
+hard 16  H  S  S  S  S  S  H  H  H  H
+hard 16 3+ S S S  S  S  S  H  H  H  S  # stands with three cards
+";
+
+  fn decide(table: &mut DecisionTable, cards: &[Value], bank: Value, allowed: &[Action]) -> Action {
+    with_view(cards, Some(bank), |view| table.action(view, allowed))
+  }
+
+  #[test]
+  fn plays_by_the_table() {
+    use Action::*;
+    use Value::*;
+    let mut table = DecisionTable::parse("small", TABLE).unwrap();
+    let all = [Hit, Stand, Double];
+    assert_eq!(decide(&mut table, &[Nine, Two], Five, &all), Double);
+    assert_eq!(decide(&mut table, &[Nine, Two], Five, &[Hit, Stand]), Hit);
+    assert_eq!(decide(&mut table, &[Ten, Six], King, &all), Hit);
+    assert_eq!(decide(&mut table, &[Ten, Four, Two], King, &all), Stand);
+    // No row for 13, nor for 19
+    assert_eq!(decide(&mut table, &[Ten, Three], Two, &all), Hit);
+    assert_eq!(decide(&mut table, &[Ten, Nine], Two, &all), Stand);
+  }
+
+  #[test]
+  fn errors_point_at_the_line_and_column() {
+    let error = |text: &str| DecisionTable::parse("bad", text).err().unwrap();
+    assert_eq!(error("bank A 2 X"), "1:10: Unknown card value 'X'");
+    assert_eq!(
+      error("hard 12 H"),
+      "1:1: Expected the bank line first, like: bank A 2 3"
+    );
+    assert_eq!(
+      error("bank A 2\n\nhard 12 H Q"),
+      "3:11: Unknown action 'Q', expected H, S, D, P, R or B"
+    );
+    assert_eq!(
+      error("bank A 2\nhard 12 H"),
+      "2:10: Expected 2 actions, one per bank card, found 1"
+    );
+    assert_eq!(
+      error("bank A 2\nhard 12 H S S"),
+      "2:13: Expected 2 actions, one per bank card"
+    );
+    assert_eq!(
+      error("bank A 2\nfirm 12 H S"),
+      "2:1: Expected hard or soft, found 'firm'"
+    );
+    assert_eq!(
+      error("bank A 2\nsoft x H S"),
+      "2:6: Expected a total, found 'x'"
+    );
+    assert_eq!(
+      error("bank A 2\nsoft 17 H S\n  soft 17 S S"),
+      "3:3: This hand already has a row at line 2"
+    );
+  }
+}
@@ -0,0 +1,526 @@
+//! The games the simulator can play. A variant decides how the cards are dealt,
+//! what players may do with their hands, when the bank draws and who wins. The
+//! round itself is played the same way for every variant.
+
+use std::fmt;
+
+use rand::seq::SliceRandom;
+
+use crate::casino::Casino;
+use crate::dutch::Dutch;
+use crate::events::{Event, Observers, Seat};
+use crate::pontoon::Pontoon;
+use crate::rules::{Outcome, Rules, Surrender};
+use crate::strategy::{Action, Actions, BankStrategy, BankView, PlayerView};
+use crate::{settle, Card, Game, Hand, Player, Value, Visibility};
+
+pub trait Variant {
+  /// Name shown with the rules.
+  fn name(&self) -> &'static str;
+
+  /// The house rules the variant is usually played with.
+  fn rules(&self) -> Rules;
+
+  /// Takes the bids and deals the starting hands in the variant's order. The
+  /// bank may be dealt one card or two.
+  fn deal(&self, game: &mut Game) -> Result<(), String>;
+
+  /// Outcome that settles a hand on the spot while the player is still
+  /// playing it, before the bank plays.
+  fn settle_early(&self, _rules: &Rules, seat: &SeatHand) -> Option<Outcome> {
+    match seat.hand.is_bust() {
+      true => Some(Outcome::Bust),
+      false => None,
+    }
+  }
+
+  /// What the player may do with the hand at `current`. Play moves on to the
+  /// next hand when standing is all that is left.
+  fn allowed_actions(
+    &self,
+    rules: &Rules,
+    bank: &Player,
+    player: &Player,
+    hands: &[SeatHand],
+    current: usize,
+  ) -> Actions;
+
+  /// Returns `true` if the bank draws another card.
+  fn bank_hits(&self, rules: &Rules, strategy: &mut dyn BankStrategy, view: &BankView) -> bool {
+    let hand = view.hand;
+    let soft_17 = rules.bank_hits_soft_17 && hand.greatest_value() == 17 && hand.is_soft();
+    soft_17 || strategy.hit(view)
+  }
+
+  /// Outcome of a hand that is still in once the bank stood or busted. A
+  /// natural still in is paid at its own rate, as a bank natural would have
+  /// taken it already.
+  fn resolve(&self, rules: &Rules, seat: &SeatHand, bank: &Hand) -> Outcome {
+    if seat.is_natural() {
+      return Outcome::Natural;
+    }
+    let player_value = seat.hand.greatest_value();
+    let bank_value = bank.greatest_value();
+    if bank.is_bust() || player_value > bank_value {
+      Outcome::Win
+    } else if player_value == bank_value {
+      rules.tie.outcome()
+    } else {
+      Outcome::Loss
+    }
+  }
+
+  /// Writes the rules only this variant uses, without a trailing newline.
+  fn fmt_rules(&self, _rules: &Rules, _f: &mut fmt::Formatter) -> fmt::Result {
+    Ok(())
+  }
+}
+
+/// Looks up a variant by the name used in `--rule variant=<name>`.
+pub fn variant_by_name(name: &str) -> Result<&'static dyn Variant, String> {
+  match name {
+    "dutch" => Ok(&Dutch),
+    "casino" => Ok(&Casino),
+    "pontoon" => Ok(&Pontoon),
+    _ => Err(format!("Expected dutch, casino or pontoon, got '{name}'")),
+  }
+}
+
+/// One of the hands a player plays, several after splitting.
+pub struct SeatHand {
+  pub hand: Hand,
+  pub bid: u32,
+  /// The bid the hand was dealt with, which doubling and buying add to
+  pub stake: u32,
+  /// Came from splitting a pair, so 21 is not a natural
+  pub split: bool,
+  /// Came from splitting Aces
+  pub split_aces: bool,
+  /// Drew an open card, after which a Pontoon player can no longer buy
+  pub twisted: bool,
+  /// Paid out before the bank played
+  pub settled: bool,
+  /// Which of the player's hands this is, counting from 0 in the order they
+  /// were split off
+  pub number: usize,
+  /// Insured against a bank natural for this much
+  pub insurance: u32,
+}
+
+impl SeatHand {
+  pub fn new(hand: Hand, bid: u32) -> SeatHand {
+    SeatHand {
+      hand,
+      bid,
+      stake: bid,
+      split: false,
+      split_aces: false,
+      twisted: false,
+      settled: false,
+      insurance: 0,
+      number: 0,
+    }
+  }
+
+  pub fn is_natural(&self) -> bool {
+    !self.split && self.hand.is_natural()
+  }
+
+  /// The player and the bank can both put up another `amount` on this hand.
+  pub fn can_raise(&self, rules: &Rules, bank: &Player, player: &Player, amount: u32) -> bool {
+    player.money >= amount && bank.money >= rules.cover(self.bid + amount) - rules.cover(self.bid)
+  }
+
+  /// The player and the bank can both put up a bid for a split off hand.
+  pub fn can_split(&self, rules: &Rules, bank: &Player, player: &Player) -> bool {
+    player.money >= self.stake && bank.money >= rules.cover(self.stake)
+  }
+}
+
+/// The bank's first card, if the players can see it.
+pub fn open_bank_card(bank: &Player) -> Option<&Card> {
+  bank
+    .hand
+    .cards()
+    .first()
+    .filter(|card| card.visibility == Visibility::All)
+}
+
+impl Game {
+  /// Plays one round of the variant in the rules and settles every bid.
+  /// Fails when the deck runs out of cards halfway.
+  pub(super) fn play_round(&mut self) -> Result<(), String> {
+    let variant = self.rules.variant;
+    self.bank_rounds_played += 1;
+    self.players.shuffle(&mut self.rng);
+    self.rounds += 1;
+    self
+      .observers
+      .emit(Event::RoundStarted { round: self.rounds });
+    variant.deal(self)?;
+
+    let mut seats = std::mem::take(&mut self.seats);
+    seats.resize_with(self.players.len(), Vec::new);
+    for (player, hands) in self.players.iter_mut().zip(&mut seats) {
+      hands.clear();
+      if player.bid != 0 {
+        let hand = std::mem::replace(&mut player.hand, Hand::new());
+        hands.push(SeatHand::new(hand, player.bid));
+        player.bid = 0;
+      }
+    }
+    let played = self.play_seats(variant, &mut seats);
+    self.seats = seats;
+    played
+  }
+
+  fn play_seats(
+    &mut self,
+    variant: &dyn Variant,
+    seats: &mut [Vec<SeatHand>],
+  ) -> Result<(), String> {
+    self.offer_insurance(seats);
+    if self.rules.surrender == Surrender::Early {
+      self.offer_early_surrender(seats);
+    }
+    if self.rules.peek && self.bank.hand.is_natural() {
+      self.bank.hand.open_all();
+      self.observers.emit(Event::bank_resolved(&self.bank.hand));
+      self.resolve_insurance(seats, true);
+      self.resolve_bank_natural(seats);
+      return Ok(());
+    }
+
+    for (index, hands) in seats.iter_mut().enumerate() {
+      self.play_hands(variant, index, hands)?;
+    }
+
+    if self.bank.hand.cards().len() < 2 {
+      let card = self.deck.deal(&mut self.bank.hand, Visibility::All)?;
+      self.observers.emit(Event::dealt(Seat::Bank, card));
+    }
+    self.bank.hand.open_all();
+    let bank_natural = self.bank.hand.is_natural();
+    self.resolve_insurance(seats, bank_natural);
+    if bank_natural {
+      self.observers.emit(Event::bank_resolved(&self.bank.hand));
+      self.resolve_bank_natural(seats);
+      return Ok(());
+    }
+    if seats.iter().flatten().any(|seat| !seat.settled) {
+      self.play_bank(variant, seats)?;
+    }
+    self.observers.emit(Event::bank_resolved(&self.bank.hand));
+
+    for (player, hands) in self.players.iter_mut().zip(seats) {
+      for seat in hands.iter_mut().filter(|seat| !seat.settled) {
+        let outcome = variant.resolve(&self.rules, seat, &self.bank.hand);
+        settle_hand(
+          &self.rules,
+          &mut self.bank,
+          player,
+          &mut self.observers,
+          seat,
+          outcome,
+        );
+      }
+    }
+    Ok(())
+  }
+
+  /// Lets players insure their first hand against a bank natural when its
+  /// open card is an Ace.
+  fn offer_insurance(&mut self, seats: &mut [Vec<SeatHand>]) {
+    let bank_ace = open_bank_card(&self.bank).is_some_and(|card| card.value() == Value::Ace);
+    if !self.rules.insurance || !bank_ace {
+      return;
+    }
+    for (player, hands) in self.players.iter_mut().zip(seats) {
+      let Some(seat) = hands.first_mut() else {
+        continue;
+      };
+      let stake = seat.bid / 2;
+      if stake == 0 || player.money < stake || self.bank.money < stake * 2 {
+        continue;
+      }
+      let strategy = self.strategies.get_mut(&player.id).unwrap();
+      let view = PlayerView {
+        hand: &seat.hand,
+        bank_card: open_bank_card(&self.bank),
+        deck: &self.deck,
+        rules: &self.rules,
+        bank_stands_on: self.bank_strategy.stands_on(),
+      };
+      if strategy.insurance(&view) {
+        player.money -= stake;
+        self.bank.money -= stake * 2;
+        seat.insurance = stake;
+      }
+    }
+  }
+
+  /// Insurance pays 2:1 if the bank has a natural and is lost otherwise.
+  fn resolve_insurance(&mut self, seats: &mut [Vec<SeatHand>], bank_natural: bool) {
+    for (player, hands) in self.players.iter_mut().zip(seats) {
+      let Some(seat) = hands.first_mut() else {
+        continue;
+      };
+      let stake = std::mem::take(&mut seat.insurance);
+      if stake == 0 {
+        continue;
+      }
+      let net = if bank_natural {
+        player.money += stake * 3;
+        stake as i64 * 2
+      } else {
+        self.bank.money += stake * 3;
+        -(stake as i64)
+      };
+      self.observers.emit(Event::Insurance {
+        player: player.id,
+        stake,
+        net,
+      });
+    }
+  }
+
+  /// Before the bank checks for a natural players may give up their hand.
+  /// Anything but surrendering declines.
+  fn offer_early_surrender(&mut self, seats: &mut [Vec<SeatHand>]) {
+    let Game {
+      deck,
+      bank,
+      players,
+      strategies,
+      rules,
+      observers,
+      bank_strategy,
+      ..
+    } = self;
+    let bank_stands_on = bank_strategy.stands_on();
+    for (player, hands) in players.iter_mut().zip(seats) {
+      let Some(seat) = hands.first_mut() else {
+        continue;
+      };
+      if seat.is_natural() {
+        continue;
+      }
+      let strategy = strategies.get_mut(&player.id).unwrap();
+      let view = PlayerView {
+        hand: &seat.hand,
+        bank_card: open_bank_card(bank),
+        deck,
+        rules,
+        bank_stands_on,
+      };
+      let allowed = [Action::Surrender, Action::Stand];
+      if strategy.action(&view, &allowed) == Action::Surrender {
+        settle_hand(rules, bank, player, observers, seat, Outcome::Surrender);
+      }
+    }
+  }
+
+  /// A bank natural takes every hand that is still in. A player natural
+  /// against it counts as a tie.
+  fn resolve_bank_natural(&mut self, seats: &mut [Vec<SeatHand>]) {
+    for (player, hands) in self.players.iter_mut().zip(seats) {
+      for seat in hands.iter_mut().filter(|seat| !seat.settled) {
+        let outcome = match seat.is_natural() {
+          true => self.rules.tie.outcome(),
+          false => Outcome::Loss,
+        };
+        settle_hand(
+          &self.rules,
+          &mut self.bank,
+          player,
+          &mut self.observers,
+          seat,
+          outcome,
+        );
+      }
+    }
+  }
+
+  /// Plays every hand of the player in seat `index`, including the hands
+  /// split off along the way.
+  fn play_hands(
+    &mut self,
+    variant: &dyn Variant,
+    index: usize,
+    hands: &mut Vec<SeatHand>,
+  ) -> Result<(), String> {
+    let Game {
+      deck,
+      bank,
+      players,
+      strategies,
+      rules,
+      observers,
+      bank_strategy,
+      ..
+    } = self;
+    let bank_stands_on = bank_strategy.stands_on();
+    let player = &mut players[index];
+    let strategy = strategies.get_mut(&player.id).unwrap();
+    let mut current = 0;
+    while current < hands.len() {
+      loop {
+        if let Some(outcome) = variant.settle_early(rules, &hands[current]) {
+          settle_hand(rules, bank, player, observers, &mut hands[current], outcome);
+          break;
+        }
+        let allowed = variant.allowed_actions(rules, bank, player, hands, current);
+        if allowed.is_empty() || allowed[..] == [Action::Stand] {
+          observers.emit(stood(player, &hands[current]));
+          break;
+        }
+        let view = PlayerView {
+          hand: &hands[current].hand,
+          bank_card: open_bank_card(bank),
+          deck,
+          rules,
+          bank_stands_on,
+        };
+        let action = match strategy.action(&view, &allowed) {
+          action if allowed.contains(&action) => action,
+          _ if allowed.contains(&Action::Stand) => Action::Stand,
+          _ => Action::Hit,
+        };
+
+        let count = hands.len();
+        let seat = &mut hands[current];
+        let to = Seat::Player {
+          id: player.id,
+          hand: seat.number,
+        };
+        match action {
+          Action::Hit => {
+            let card = deck.deal(&mut seat.hand, Visibility::All)?;
+            observers.emit(Event::dealt(to, card));
+            seat.twisted = true;
+          }
+          Action::Stand => {
+            observers.emit(stood(player, seat));
+            break;
+          }
+          Action::Buy => {
+            player.money -= seat.stake;
+            bank.money -= rules.cover(seat.bid + seat.stake) - rules.cover(seat.bid);
+            seat.bid += seat.stake;
+            let card = deck.deal(&mut seat.hand, Visibility::Owner)?;
+            observers.emit(Event::dealt(to, card));
+          }
+          Action::Double => {
+            player.money -= seat.bid;
+            bank.money -= rules.cover(seat.bid * 2) - rules.cover(seat.bid);
+            seat.bid *= 2;
+            let card = deck.deal(&mut seat.hand, Visibility::All)?;
+            observers.emit(Event::dealt(to, card));
+            match variant.settle_early(rules, seat) {
+              Some(outcome) => settle_hand(rules, bank, player, observers, seat, outcome),
+              None => observers.emit(stood(player, seat)),
+            }
+            break;
+          }
+          Action::Split => {
+            player.money -= seat.stake;
+            bank.money -= rules.cover(seat.stake);
+            let [first, second] = [seat.hand.cards()[0], seat.hand.cards()[1]];
+            let aces = first.value() == Value::Ace;
+            seat.split = true;
+            seat.split_aces = aces;
+            seat.hand.clear();
+            seat.hand.add_card(first, &rules.worth);
+            let card = deck.deal(&mut seat.hand, Visibility::All)?;
+            observers.emit(Event::dealt(to, card));
+
+            let mut hand = Hand::new();
+            hand.add_card(second, &rules.worth);
+            let card = deck.deal(&mut hand, Visibility::All)?;
+            let to = Seat::Player {
+              id: player.id,
+              hand: count,
+            };
+            observers.emit(Event::dealt(to, card));
+            let mut split_off = SeatHand::new(hand, seat.stake);
+            split_off.split = true;
+            split_off.split_aces = aces;
+            split_off.number = count;
+            hands.insert(current + 1, split_off);
+          }
+          Action::Surrender => {
+            settle_hand(rules, bank, player, observers, seat, Outcome::Surrender);
+            break;
+          }
+        }
+      }
+      current += 1;
+    }
+    Ok(())
+  }
+
+  fn play_bank(&mut self, variant: &dyn Variant, seats: &[Vec<SeatHand>]) -> Result<(), String> {
+    loop {
+      let hand = &self.bank.hand;
+      if hand.is_bust() {
+        break;
+      }
+      let view = BankView::new(hand, seats, &self.deck, &self.rules);
+      if !variant.bank_hits(&self.rules, self.bank_strategy.as_mut(), &view) {
+        break;
+      }
+      let card = self.deck.deal(&mut self.bank.hand, Visibility::All)?;
+      self.observers.emit(Event::dealt(Seat::Bank, card));
+    }
+    Ok(())
+  }
+}
+
+/// The player standing on the hand in `seat`.
+fn stood(player: &Player, seat: &SeatHand) -> Event {
+  Event::PlayerStood {
+    player: player.id,
+    hand: seat.number,
+    total: seat.hand.greatest_value(),
+  }
+}
+
+/// Pays out one of a player's hands. `settle` works on the player's bid, so
+/// the hand's bid takes its place for the payout.
+fn settle_hand(
+  rules: &Rules,
+  bank: &mut Player,
+  player: &mut Player,
+  observers: &mut Observers,
+  seat: &mut SeatHand,
+  outcome: Outcome,
+) {
+  let (id, hand) = (player.id, seat.number);
+  match outcome {
+    Outcome::Bust => observers.emit(Event::PlayerBust {
+      player: id,
+      hand,
+      total: seat.hand.greatest_value(),
+    }),
+    Outcome::Charlie => observers.emit(Event::CharlieWin {
+      player: id,
+      hand,
+      cards: seat.hand.cards().len(),
+    }),
+    _ => {}
+  }
+  let bid = seat.bid;
+  player.bid = bid;
+  let net = settle(rules, bank, player, outcome);
+  seat.settled = true;
+  let cards = seat.hand.cards();
+  observers.emit(Event::Payout {
+    player: id,
+    hand,
+    start: (cards[0].value(), cards[1].value()),
+    cards: cards.len(),
+    outcome,
+    bid,
+    net,
+  });
+}